@@ -0,0 +1,108 @@
+use std::collections::BTreeSet;
+
+/// A partial assignment of values to variables, stored densely by variable so that looking up a
+/// variable's value takes constant time. It holds the same information as an interpretation, i.e.
+/// the set of literals that are true, which it converts to and from
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Assignment {
+    // The value of each variable, indexed by variable. Index 0 is never used, as there's no
+    // variable 0
+    values: Vec<Option<bool>>,
+}
+
+impl Assignment {
+    pub fn new() -> Assignment {
+        Assignment::default()
+    }
+
+    /// Returns the value of the variable, or None if it isn't assigned
+    pub fn value(&self, variable: usize) -> Option<bool> {
+        self.values.get(variable).cloned().unwrap_or(None)
+    }
+
+    /// Returns the value of the literal, or None if its variable isn't assigned
+    pub fn literal_value(&self, literal: isize) -> Option<bool> {
+        self.value(literal.unsigned_abs()).map(|value| value == (literal > 0))
+    }
+
+    /// Makes the literal true, replacing any value its variable had
+    pub fn set(&mut self, literal: isize) {
+        let variable = literal.unsigned_abs();
+        if variable >= self.values.len() {
+            self.values.resize(variable + 1, None);
+        }
+        self.values[variable] = Some(literal > 0);
+    }
+
+    /// Makes the variable unassigned
+    pub fn unset(&mut self, variable: usize) {
+        if let Some(value) = self.values.get_mut(variable) {
+            *value = None;
+        }
+    }
+
+    /// Returns the amount of assigned variables
+    pub fn len(&self) -> usize {
+        self.values.iter().filter(|value| value.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the literals that are true under the assignment, i.e. it as an interpretation
+    pub fn to_model(&self) -> BTreeSet<isize> {
+        self.values.iter().enumerate().filter_map(|(variable, value)| {
+            value.map(|value| if value { variable as isize } else { -(variable as isize) })
+        }).collect()
+    }
+}
+
+/// Assigns every literal of the interpretation. If it has both literals of a variable, the
+/// positive one wins
+impl<'a> From<&'a BTreeSet<isize>> for Assignment {
+    fn from(interpretation: &'a BTreeSet<isize>) -> Assignment {
+        let mut assignment = Assignment::new();
+        for &literal in interpretation {
+            assignment.set(literal);
+        }
+        assignment
+    }
+}
+
+impl From<Assignment> for BTreeSet<isize> {
+    fn from(assignment: Assignment) -> BTreeSet<isize> {
+        assignment.to_model()
+    }
+}
+
+#[test]
+fn test_assignment() {
+    let mut assignment = Assignment::new();
+    assert!(assignment.is_empty());
+    assert_eq!(None, assignment.value(3));
+    assignment.set(3);
+    assignment.set(-1);
+    assert_eq!(Some(true), assignment.value(3));
+    assert_eq!(Some(false), assignment.value(1));
+    assert_eq!(None, assignment.value(2));
+    assert_eq!(Some(false), assignment.literal_value(-3));
+    assert_eq!(Some(true), assignment.literal_value(-1));
+    assert_eq!(2, assignment.len());
+
+    assignment.set(-3);
+    assert_eq!(Some(false), assignment.value(3));
+    assignment.unset(3);
+    assignment.unset(100);
+    assert_eq!(None, assignment.value(3));
+    assert_eq!(vec![-1].into_iter().collect::<BTreeSet<_>>(), assignment.to_model());
+}
+
+#[test]
+fn test_assignment_round_trip() {
+    let model: BTreeSet<isize> = vec![-7, -2, 1, 4].into_iter().collect();
+    let assignment = Assignment::from(&model);
+    assert_eq!(model, assignment.to_model());
+    assert_eq!(model, BTreeSet::from(assignment));
+    assert_eq!(BTreeSet::new(), Assignment::from(&BTreeSet::new()).to_model());
+}
@@ -0,0 +1,117 @@
+use std::collections::BTreeSet;
+use cnf_system::{CNFSystem, ClauseType};
+
+/// The most variables `brute_force` will enumerate the assignments of
+pub const BRUTE_FORCE_MAX_VARIABLES: usize = 24;
+
+/// An iterator over all 2^n total assignments of a set of variables, each given as the set of
+/// literals that are true in it
+pub struct Assignments {
+    variables: Vec<usize>,
+    // The bits of this number are the polarities of the variables in the next assignment
+    next_assignment: u64,
+}
+
+impl Assignments {
+    pub fn new(variables: &BTreeSet<usize>) -> Assignments {
+        assert!(variables.len() < 64, "can't enumerate the assignments of 64 or more variables");
+        Assignments{ variables: variables.iter().cloned().collect(), next_assignment: 0 }
+    }
+}
+
+impl Iterator for Assignments {
+    type Item = BTreeSet<isize>;
+
+    fn next(&mut self) -> Option<BTreeSet<isize>> {
+        if self.next_assignment >> self.variables.len() != 0 {
+            return None;
+        }
+        let assignment = self.variables.iter().enumerate().map(|(index, &variable)| {
+            if self.next_assignment & (1 << index) == 0 {
+                -(variable as isize)
+            } else {
+                variable as isize
+            }
+        }).collect();
+        self.next_assignment += 1;
+        Some(assignment)
+    }
+}
+
+/// Finds if the system is Satisfiable or Unsatisfiable by trying every assignment of its
+/// variables, returning the first model found. This is only meant as a trivially correct
+/// reference for the other solvers, so it panics if the system has more than
+/// `BRUTE_FORCE_MAX_VARIABLES` variables
+pub fn brute_force(system: &CNFSystem) -> (ClauseType, Option<BTreeSet<isize>>) {
+    let variables = system.variables();
+    assert!(variables.len() <= BRUTE_FORCE_MAX_VARIABLES,
+            "brute_force() refuses systems with more than {} variables",
+            BRUTE_FORCE_MAX_VARIABLES);
+    let model = Assignments::new(&variables).find(|assignment| {
+        system.clauses.iter().all(|clause| {
+            clause.iter().any(|literal| assignment.contains(literal))
+        })
+    });
+    match model {
+        Some(model) => (ClauseType::Satisfiable, Some(model)),
+        None        => (ClauseType::Unsatisfiable, None),
+    }
+}
+
+#[test]
+fn test_assignments() {
+    let variables: BTreeSet<usize> = vec![2, 5].into_iter().collect();
+    let assignments = Assignments::new(&variables).collect::<Vec<_>>();
+    assert_eq!(4, assignments.len());
+    for &(literal2, literal5) in &[(2, 5), (-2, 5), (2, -5), (-2, -5)] {
+        let assignment: BTreeSet<isize> = vec![literal2, literal5].into_iter().collect();
+        assert!(assignments.contains(&assignment));
+    }
+
+    // The empty set of variables only has the empty assignment
+    assert_eq!(vec![BTreeSet::new()], Assignments::new(&BTreeSet::new()).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_brute_force() {
+    use cnf_system::CNFClause;
+    use testutil::satisfies;
+
+    // (1 ∨ 2) ∧ (¬1)
+    let mut system = CNFSystem::new(None);
+    let mut clause = CNFClause::new();
+    clause.add(1);
+    clause.add(2);
+    system.add_clause(clause);
+    let mut clause = CNFClause::new();
+    clause.add(-1);
+    system.add_clause(clause);
+    let (clause_type, model) = brute_force(&system);
+    assert_eq!(ClauseType::Satisfiable, clause_type);
+    assert!(satisfies(&system, &model.unwrap()));
+
+    // ...and with (¬2) it can't be satisfied anymore
+    let mut clause = CNFClause::new();
+    clause.add(-2);
+    system.add_clause(clause);
+    assert_eq!((ClauseType::Unsatisfiable, None), brute_force(&system));
+}
+
+#[test]
+fn test_brute_force_against_basic_dpll() {
+    use dpll::basic_dpll;
+    use testutil::{XorShift, random_system, satisfies};
+
+    let mut rng = XorShift::new(102);
+    for _ in 0..200 {
+        let clause_count = rng.below(30) as usize + 1;
+        let system = random_system(&mut rng, 6, clause_count, 3);
+        let (expected_type, model) = brute_force(&system);
+        let (clause_type, interpretation) = basic_dpll(system.clone());
+        assert_eq!(expected_type, clause_type, "verdicts differ on {:?}", system);
+        if let Some(model) = model {
+            assert!(satisfies(&system, &model));
+            assert!(satisfies(&system, &interpretation));
+        }
+    }
+}
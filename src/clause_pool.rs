@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use cnf_system::CNFClause;
+
+/// Identifies a clause in a ClausePool. Ids of the same pool are equal exactly when their clauses
+/// are, so comparing ids is enough to compare clauses
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ClauseId(usize);
+
+/// Stores each distinct clause once and gives it a stable ClauseId, so that code passing clauses
+/// around doesn't have to copy or rehash them. Clauses are never removed, but a pool can be given
+/// a limit on how many it holds
+#[derive(Clone, Debug, Default)]
+pub struct ClausePool {
+    clauses: Vec<CNFClause>,
+    // The ids of the clauses with each hash, which is usually just one
+    ids_by_hash: HashMap<u64, Vec<ClauseId>>,
+    limit: Option<usize>,
+}
+
+impl ClausePool {
+    pub fn new() -> ClausePool {
+        ClausePool::default()
+    }
+
+    /// Returns a pool that holds at most `limit` clauses
+    pub fn with_limit(limit: usize) -> ClausePool {
+        ClausePool{ limit: Some(limit), ..ClausePool::default() }
+    }
+
+    /// Returns the id of the clause, adding it to the pool if it's new. Returns None if it's new
+    /// but the pool is already full
+    pub fn intern(&mut self, clause: CNFClause) -> Option<ClauseId> {
+        let hash = hash_of(&clause);
+        if let Some(id) = self.find(hash, &clause) {
+            return Some(id);
+        }
+        if self.limit.is_some_and(|limit| self.clauses.len() >= limit) {
+            return None;
+        }
+        let id = ClauseId(self.clauses.len());
+        self.clauses.push(clause);
+        self.ids_by_hash.entry(hash).or_default().push(id);
+        Some(id)
+    }
+
+    /// Returns the id of the clause if it's in the pool
+    pub fn id_of(&self, clause: &CNFClause) -> Option<ClauseId> {
+        self.find(hash_of(clause), clause)
+    }
+
+    fn find(&self, hash: u64, clause: &CNFClause) -> Option<ClauseId> {
+        self.ids_by_hash.get(&hash)?.iter().find(|id| self.get(**id) == clause).cloned()
+    }
+
+    /// Returns the clause with the given id. Panics if the id is from a different pool
+    pub fn get(&self, id: ClauseId) -> &CNFClause {
+        &self.clauses[id.0]
+    }
+
+    /// Returns the amount of distinct clauses in the pool
+    pub fn len(&self) -> usize {
+        self.clauses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+}
+
+fn hash_of(clause: &CNFClause) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    clause.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn test_clause_pool() {
+    let clause = CNFClause::from_literals(&[1, -2, 3]).unwrap();
+    let other_clause = CNFClause::from_literals(&[-2]).unwrap();
+    let mut pool = ClausePool::new();
+    let id = pool.intern(clause.clone()).unwrap();
+    assert_eq!(Some(id), pool.intern(CNFClause::from_literals(&[3, 1, -2]).unwrap()));
+    let other_id = pool.intern(other_clause.clone()).unwrap();
+    assert!(id != other_id);
+    assert_eq!(2, pool.len());
+    assert_eq!(&clause, pool.get(id));
+    assert_eq!(Some(other_id), pool.id_of(&other_clause));
+    assert_eq!(None, pool.id_of(&CNFClause::new()));
+
+    // A full pool still gives out the ids of the clauses it has
+    let mut pool = ClausePool::with_limit(1);
+    let id = pool.intern(clause.clone()).unwrap();
+    assert_eq!(None, pool.intern(other_clause));
+    assert_eq!(Some(id), pool.intern(clause));
+    assert_eq!(1, pool.len());
+}
@@ -1,5 +1,11 @@
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::collections::btree_set::Iter;
+use std::convert::TryFrom;
+use std::fmt;
+use std::num::TryFromIntError;
+use std::hash::{Hash, Hasher};
+use parser::{parse_dimacs_bytes, ParseError, ParseOptions};
+use var_map::VarAllocator;
 
 #[allow(dead_code)]
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
@@ -7,22 +13,43 @@ pub enum ClauseType {
     Tautology,      // Always true    -- all interpretations are models
     Satisfiable,    // Sometimes true -- has some models
     Unsatisfiable,  // Never true     -- no models
+    Unknown,        // Not decided    -- the solver was stopped before it found out
 }
 
-#[derive(Eq, PartialEq, Hash, Clone, Debug)]
-/// A clause in clausal normal form (CNF) i.e. a disjunction (∨) of literals
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Debug, Default)]
+/// A clause in clausal normal form (CNF) i.e. a disjunction (∨) of literals. Clauses are ordered
+/// by comparing their sorted literals lexicographically
 pub struct CNFClause {
     // Ordered set of literals, as order doesn't matter and the amount of times a literal occurs
     // doesn't matter: (a ∨ a) <=> (a)
     literals: BTreeSet<isize>,
 }
 
+/// Returned when trying to build a clause containing zero, which isn't a valid literal
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct ZeroLiteralError;
+
+impl fmt::Display for ZeroLiteralError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a clause can't contain a literal of value zero")
+    }
+}
+
 impl CNFClause {
 
     pub fn new() -> CNFClause {
         CNFClause{ literals: BTreeSet::new() }
     }
 
+    /// Builds a clause from a list of literals, returning an error rather than panicking like
+    /// `add` if any of them is zero
+    pub fn from_literals(literals: &[isize]) -> Result<CNFClause, ZeroLiteralError> {
+        if literals.contains(&0) {
+            return Err(ZeroLiteralError);
+        }
+        Ok(CNFClause{ literals: literals.iter().cloned().collect() })
+    }
+
     /// Add a new literal to the clause
     /// Returns true if value was not already present in the set
     pub fn add(&mut self, literal: isize) -> bool {
@@ -34,11 +61,11 @@ impl CNFClause {
 
     /// Removes a literal from the set, returning true if value was present in the set
     pub fn remove(&mut self, literal: &isize) -> bool {
-        self.literals.remove(&literal)
+        self.literals.remove(literal)
     }
 
     /// Returns an iterator over the literals
-    pub fn iter(&self) -> Iter<isize> {
+    pub fn iter(&self) -> Iter<'_, isize> {
         self.literals.iter()
     }
 
@@ -47,13 +74,92 @@ impl CNFClause {
         self.literals.contains(&literal)
     }
 
+    /// Returns true if this clause contains either literal of the variable
+    pub fn contains_var(&self, variable: usize) -> bool {
+        let literal = variable as isize;
+        self.contains(literal) || self.contains(-literal)
+    }
+
     /// Returns the amount of elements in the set
     pub fn len(&self) -> usize {
         self.literals.len()
     }
 
+    /// Returns true if the clause has no literals i.e. it can't be satisfied
+    pub fn is_empty(&self) -> bool {
+        self.literals.is_empty()
+    }
+
+    /// Returns the resolvent of this clause, which contains `literal`, and `other`, which contains
+    /// its negation: every literal of both clauses except `literal` and its negation
+    pub fn resolve(&self, other: &CNFClause, literal: isize) -> CNFClause {
+        debug_assert!(self.contains(literal) && other.contains(-literal));
+        let mut resolvent = self.union(other);
+        resolvent.remove(&literal);
+        resolvent.remove(&-literal);
+        resolvent
+    }
+
+    /// Returns the clause of the literals in both clauses
+    pub fn intersection(&self, other: &CNFClause) -> CNFClause {
+        CNFClause{ literals: self.literals.intersection(&other.literals).cloned().collect() }
+    }
+
+    /// Returns the clause of the literals in either clause, i.e. their disjunction
+    pub fn union(&self, other: &CNFClause) -> CNFClause {
+        CNFClause{ literals: self.literals.union(&other.literals).cloned().collect() }
+    }
+
+    /// Returns the literal of a unit clause i.e. one with exactly one literal, or None if the
+    /// clause isn't a unit
+    pub fn get_unit(&self) -> Option<isize> {
+        if self.len() == 1 { self.literals.iter().next().cloned() } else { None }
+    }
+
+    /// Returns true if the clause contains some literal and its negation, i.e. (a ∨ ¬a)
+    pub fn is_tautology(&self) -> bool {
+        self.literals.iter().any(|literal| self.literals.contains(&-literal))
+    }
+
+    /// Returns true if the model makes some literal of the clause true. Any variable the model
+    /// leaves out is unassigned, and so makes none of its literals true
+    pub fn is_satisfied_by(&self, model: &BTreeSet<isize>) -> bool {
+        self.literals.iter().any(|literal| model.contains(literal))
+    }
+
+    /// Returns the clause in logic notation, e.g. (x1 ∨ ¬x2), with its literals in order of
+    /// variable, or ⊥ if it's empty
+    pub fn pretty(&self) -> String {
+        if self.is_empty() {
+            return "⊥".to_string();
+        }
+        let mut literals = self.literals.iter().cloned().collect::<Vec<_>>();
+        literals.sort_by_key(|&literal| (literal.unsigned_abs(), literal < 0));
+        let literals = literals.into_iter().map(|literal| {
+            format!("{}x{}", if literal < 0 { "¬" } else { "" }, literal.unsigned_abs())
+        }).collect::<Vec<_>>();
+        format!("({})", literals.join(" ∨ "))
+    }
+
+    /// Returns true if every literal of this clause is also in `other`, so whenever this clause
+    /// is satisfied `other` is too
+    pub fn subsumes(&self, other: &CNFClause) -> bool {
+        self.literals.is_subset(&other.literals)
+    }
+
 } // impl CNFClause
 
+impl Hash for CNFClause {
+    /// Hashes the sorted literals, so clauses that are == always hash the same however they're
+    /// stored
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for literal in self.iter() {
+            literal.hash(state);
+        }
+    }
+}
+
 #[test]
 fn test_cnf_clause() {
     let t1: isize = 1;
@@ -115,26 +221,173 @@ fn test_cnf_clause() {
 }
 
 
+#[test]
+fn test_cnf_clause_from_literals() {
+    let mut clause = CNFClause::new();
+    clause.add(3);
+    clause.add(-1);
+    assert_eq!(Ok(clause), CNFClause::from_literals(&[-1, 3, -1]));
+    assert_eq!(Ok(CNFClause::new()), CNFClause::from_literals(&[]));
+
+    assert_eq!(Err(ZeroLiteralError), CNFClause::from_literals(&[1, 0, 2]));
+    assert_eq!(Err(ZeroLiteralError), CNFClause::from_literals(&[0]));
+}
+
+#[test]
+fn test_cnf_clause_hash() {
+    use std::collections::hash_map::DefaultHasher;
+
+    let hash_of = |clause: &CNFClause| {
+        let mut hasher = DefaultHasher::new();
+        clause.hash(&mut hasher);
+        hasher.finish()
+    };
+    let clause = CNFClause::from_literals(&[3, -1, 2]).unwrap();
+    let other_clause = CNFClause::from_literals(&[2, 3, -1, 3]).unwrap();
+    assert_eq!(clause, other_clause);
+    assert_eq!(hash_of(&clause), hash_of(&other_clause));
+    assert!(hash_of(&clause) != hash_of(&CNFClause::from_literals(&[3, 1, 2]).unwrap()));
+
+    let mut clauses = HashSet::new();
+    clauses.insert(clause);
+    assert!(clauses.contains(&other_clause));
+    assert!(!clauses.insert(other_clause));
+}
+
+#[test]
+fn test_cnf_clause_resolve() {
+    let clause = CNFClause::from_literals(&[1, 2, -3]).unwrap();
+    let other = CNFClause::from_literals(&[-1, 2, 4]).unwrap();
+    assert_eq!(CNFClause::from_literals(&[2, -3, 4]).unwrap(), clause.resolve(&other, 1));
+    assert_eq!(CNFClause::from_literals(&[2, -3, 4]).unwrap(), other.resolve(&clause, -1));
+
+    let unit = CNFClause::from_literals(&[1]).unwrap();
+    let negated_unit = CNFClause::from_literals(&[-1]).unwrap();
+    assert_eq!(CNFClause::new(), unit.resolve(&negated_unit, 1));
+}
+
+#[test]
+fn test_cnf_clause_contains_var() {
+    let clause = CNFClause::from_literals(&[1, -3]).unwrap();
+    assert!(clause.contains_var(3));
+    assert!(clause.contains_var(1));
+    assert!(!clause.contains(3));
+    assert!(!clause.contains_var(2));
+    assert!(!CNFClause::new().contains_var(1));
+}
+
+#[test]
+fn test_cnf_clause_intersection_union() {
+    let clause = CNFClause::from_literals(&[1, 2, -3]).unwrap();
+    let other = CNFClause::from_literals(&[-1, 2, -3, 4]).unwrap();
+    assert_eq!(CNFClause::from_literals(&[2, -3]).unwrap(), clause.intersection(&other));
+    assert_eq!(CNFClause::from_literals(&[-1, 1, 2, -3, 4]).unwrap(), clause.union(&other));
+    assert_eq!(clause.union(&other), other.union(&clause));
+
+    // Disjoint clauses
+    let disjoint = CNFClause::from_literals(&[5, -6]).unwrap();
+    assert_eq!(CNFClause::new(), clause.intersection(&disjoint));
+    assert_eq!(CNFClause::from_literals(&[1, 2, -3, 5, -6]).unwrap(), clause.union(&disjoint));
+    assert_eq!(clause, clause.union(&CNFClause::new()));
+}
+
+#[test]
+fn test_cnf_clause_ordering() {
+    let mut clause12 = CNFClause::new();
+    clause12.add(2);
+    clause12.add(1);
+    let mut clause13 = CNFClause::new();
+    clause13.add(1);
+    clause13.add(3);
+    assert!(clause12 < clause13);
+
+    // A clause comes before every clause it's a proper prefix of, and negative literals sort first
+    let mut clause1 = CNFClause::new();
+    clause1.add(1);
+    assert!(clause1 < clause12);
+    let mut clause_minus1 = CNFClause::new();
+    clause_minus1.add(-1);
+    clause_minus1.add(5);
+    assert!(clause_minus1 < clause1);
+    assert!(CNFClause::new() < clause_minus1);
+}
+
+/// What happened when a clause was added to a system
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum AddOutcome {
+    Added,
+    /// The clause was already in the system, which is left as it was
+    Duplicate,
+    /// The clause was added and is a unit, with this literal
+    Unit(isize),
+    /// The clause was added and has no literals, so the system is now Unsatisfiable
+    Empty,
+}
+
+/// The size of a system before and after `CNFSystem::simplify`, and what each step removed
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimplifyReport {
+    pub clauses_before: usize,
+    pub clauses_after: usize,
+    pub variables_before: usize,
+    pub variables_after: usize,
+    pub literals_before: usize,
+    pub literals_after: usize,
+    pub tautologies_removed: usize,
+    pub subsumed_removed: usize,
+    /// The literals that unit propagation forced, which no longer occur in the system
+    pub forced_literals: BTreeSet<isize>,
+    /// True if unit propagation showed the system is Unsatisfiable, in which case it's left as
+    /// just the empty clause
+    pub unsatisfiable: bool,
+}
 
 /// A conjunction (∧) of clauses
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct CNFSystem {
     pub clauses: HashSet<CNFClause>,
+    /// Comment lines carried along with the system, without their leading 'c'. These are only
+    /// filled in when asked for while parsing
+    pub comments: Vec<String>,
 }
 
 impl CNFSystem {
     pub fn new(initial_clauses: Option<HashSet<CNFClause>>) -> CNFSystem {
         match initial_clauses {
-            Some(c) => CNFSystem{ clauses: c, },
-            None    => CNFSystem{ clauses: HashSet::new(), },
+            Some(c) => CNFSystem{ clauses: c, comments: vec![], },
+            None    => CNFSystem{ clauses: HashSet::new(), comments: vec![], },
         }
     }
 
+    /// Reads a system in DIMACS form from bytes in memory, e.g. received over a network, without
+    /// copying them into a String first. Returns the system along with the units of the input.
+    /// See `parser::parse_dimacs_bytes`
+    pub fn from_bytes(input: &[u8]) -> Result<(CNFSystem, HashSet<isize>), ParseError> {
+        let parsed = parse_dimacs_bytes(input, &ParseOptions::default())?;
+        Ok((parsed.system, parsed.units))
+    }
+
     /// Add a clause to the system. Returns false if the value was already in the system
     pub fn add_clause(&mut self, clause: CNFClause) -> bool {
         self.clauses.insert(clause)
     }
 
+    /// add_clause(), telling the caller whether the clause was new and whether it was a unit or
+    /// empty
+    pub fn add_clause_reporting(&mut self, clause: CNFClause) -> AddOutcome {
+        let unit = clause.get_unit();
+        let is_empty = clause.is_empty();
+        if !self.add_clause(clause) {
+            AddOutcome::Duplicate
+        } else if let Some(literal) = unit {
+            AddOutcome::Unit(literal)
+        } else if is_empty {
+            AddOutcome::Empty
+        } else {
+            AddOutcome::Added
+        }
+    }
+
     /// Removes a clause from the system. Returns false if the value wasn't already in the system
     pub fn remove_clause(&mut self, clause: &CNFClause) -> bool {
         self.clauses.remove(clause)
@@ -144,4 +397,1012 @@ impl CNFSystem {
     pub fn len(&self) -> usize {
         self.clauses.len()
     }
+
+    /// Returns true if there are no clauses in the system
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    /// Applies unit propagation of a literal l to the system.
+    ///     If a clause contains: l, then remove that entire clause
+    ///     If a clause contains: not(l), then remove not(l) from the clause but keep the others
+    /// Returns Some(new_units) if successful, None if a clause became empty i.e. contradicts l.
+    /// A tautology contains both l and not(l), so it's removed like any other clause containing l
+    /// and never reduced. That's why the solvers don't need tautologies taken out beforehand
+    pub fn propagate(&mut self, literal: isize) -> Option<HashSet<isize>> {
+        let mut new_units = HashSet::new();
+        let mut clauses_to_remove: Vec<CNFClause> = vec![];
+        let mut clauses_to_reduce: Vec<CNFClause> = vec![];
+
+        for each_clause in self.clauses.iter().cloned() {
+            if each_clause.contains(literal) {
+                clauses_to_remove.push(each_clause);
+            } else if each_clause.contains(-literal) {
+                clauses_to_reduce.push(each_clause);
+            }
+        }
+
+        for each_clause in clauses_to_remove {
+            self.remove_clause(&each_clause);
+        }
+
+        for mut each_clause in clauses_to_reduce {
+            // Have to remove and then add because it's a hash
+            // Check if successful because it could have been removed by the clauses_to_remove
+            // vector
+            if self.remove_clause(&each_clause) {
+                each_clause.remove(&-literal);
+                match each_clause.len() {
+                    0 => { return None; },
+                    1 => {
+                        new_units.insert(*each_clause.iter()          // get literals iterator
+                                                     .next().unwrap() // get first literal
+                                        );
+                    },
+                    _ => {},
+                };
+                self.add_clause(each_clause);
+            }
+        }
+        Some(new_units)
+    }
+
+    /// Propagates the unit clauses of the system, and the units that propagating them reveals,
+    /// until there are none left. Returns the literals that were forced this way, or Err if they
+    /// contradict each other or the system already has an empty clause i.e. it's Unsatisfiable
+    #[allow(clippy::result_unit_err)]
+    pub fn unit_simplify(&mut self) -> Result<BTreeSet<isize>, ()> {
+        if self.clauses.iter().any(CNFClause::is_empty) {
+            return Err(());
+        }
+        let mut forced_literals = BTreeSet::new();
+        let mut current_units = self.units();
+        while !current_units.is_empty() {
+            // The new units revealed by previous unit propagation
+            let mut revealed_units = HashSet::new();
+            for each_unit_literal in current_units {
+                match self.propagate(each_unit_literal) {
+                    None            => return Err(()),
+                    Some(new_units) => {
+                        revealed_units.extend(new_units);
+                        forced_literals.insert(each_unit_literal);
+                    },
+                }
+            }
+            current_units = revealed_units;
+        }
+        Ok(forced_literals)
+    }
+
+    /// Returns the polarity each variable should first be decided with: true if more clauses
+    /// contain its positive literal than its negative one, false otherwise
+    pub fn initial_phases(&self) -> HashMap<usize, bool> {
+        // The number of positive occurrences minus the number of negative ones
+        let mut balances: HashMap<usize, isize> = HashMap::new();
+        for literal in self.clauses.iter().flat_map(|clause| clause.iter()) {
+            *balances.entry(literal.unsigned_abs()).or_insert(0) += literal.signum();
+        }
+        balances.into_iter().map(|(variable, balance)| (variable, balance > 0)).collect()
+    }
+
+    /// Splits the system into the smallest subsystems that don't share any variables, so that each
+    /// one can be solved on its own. An empty clause has no variables so it ends up alone
+    pub fn connected_components(&self) -> Vec<CNFSystem> {
+        // Union-find over the variables, where the variables of each clause get joined together
+        let mut parents: HashMap<usize, usize> = HashMap::new();
+        fn find(parents: &mut HashMap<usize, usize>, variable: usize) -> usize {
+            let parent = *parents.entry(variable).or_insert(variable);
+            if parent == variable {
+                return variable;
+            }
+            let root = find(parents, parent);
+            parents.insert(variable, root);
+            root
+        }
+        for each_clause in &self.clauses {
+            let mut variables = each_clause.iter().map(|literal| literal.unsigned_abs());
+            if let Some(first_variable) = variables.next() {
+                let first_root = find(&mut parents, first_variable);
+                for each_variable in variables {
+                    let root = find(&mut parents, each_variable);
+                    parents.insert(root, first_root);
+                }
+            }
+        }
+
+        let mut components: HashMap<usize, CNFSystem> = HashMap::new();
+        let mut empty_clause_components = vec![];
+        for each_clause in &self.clauses {
+            match each_clause.iter().next() {
+                Some(literal) => {
+                    let root = find(&mut parents, literal.unsigned_abs());
+                    components.entry(root)
+                              .or_insert_with(|| CNFSystem::new(None))
+                              .add_clause(each_clause.clone());
+                },
+                None => {
+                    let mut component = CNFSystem::new(None);
+                    component.add_clause(each_clause.clone());
+                    empty_clause_components.push(component);
+                },
+            }
+        }
+        components.into_values().chain(empty_clause_components).collect()
+    }
+
+    /// Returns how many clauses there are of each length
+    pub fn clause_length_histogram(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for each_clause in &self.clauses {
+            *histogram.entry(each_clause.len()).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Returns true if every clause has at most one positive literal
+    pub fn is_horn(&self) -> bool {
+        self.clauses.iter().all(|clause| clause.iter().filter(|&&literal| literal > 0).count() <= 1)
+    }
+
+    /// Returns true if every clause has at most two literals
+    pub fn is_2sat(&self) -> bool {
+        self.clauses.iter().all(|clause| clause.len() <= 2)
+    }
+
+    /// Adds every resolvent of two clauses of the system that isn't a tautology and has at most
+    /// `max_len` literals, along with the resolvents of those, until there are no new ones. This is
+    /// exponential in general, hence the bound. Stops early if the empty clause is derived, as the
+    /// system is then known to be Unsatisfiable. Returns the number of clauses added
+    pub fn resolution_closure(&mut self, max_len: usize) -> usize {
+        let mut added_count = 0;
+        loop {
+            let clauses = self.clauses.iter().cloned().collect::<Vec<_>>();
+            let mut resolvents = vec![];
+            for (index, each_clause) in clauses.iter().enumerate() {
+                for other_clause in &clauses[index + 1..] {
+                    for &literal in each_clause.iter().filter(|&&l| other_clause.contains(-l)) {
+                        let resolvent = each_clause.resolve(other_clause, literal);
+                        if resolvent.len() <= max_len && !resolvent.is_tautology() {
+                            resolvents.push(resolvent);
+                        }
+                    }
+                }
+            }
+            let mut added_any = false;
+            for each_resolvent in resolvents {
+                let is_empty = each_resolvent.is_empty();
+                if self.add_clause(each_resolvent) {
+                    added_count += 1;
+                    added_any = true;
+                    if is_empty {
+                        return added_count;
+                    }
+                }
+            }
+            if !added_any {
+                return added_count;
+            }
+        }
+    }
+
+    /// Adds the binary clauses found by hyper-binary resolution, returning how many were added.
+    /// Each literal d of the system is set in turn and unit propagated, and whenever a clause of
+    /// three or more literals is what forces some literal u, the shortcut (¬d ∨ u) is added so
+    /// that later propagation gets there in one step. A literal whose propagation reaches an empty
+    /// clause gives no shortcuts, as there is nothing sensible to add but its negation. The
+    /// clauses are visited in order, so which clause forces a literal, and so the shortcuts added,
+    /// doesn't depend on how the system was hashed
+    pub fn hyper_binary_resolve(&mut self) -> usize {
+        let mut clauses = self.clauses.iter().cloned().collect::<Vec<_>>();
+        clauses.sort();
+        let mut binaries = vec![];
+        for variable in self.variables() {
+            for &decision in &[variable as isize, -(variable as isize)] {
+                let mut assigned: HashSet<isize> = HashSet::new();
+                assigned.insert(decision);
+                let mut implied = vec![];
+                let mut conflict = false;
+                let mut changed = true;
+                while changed && !conflict {
+                    changed = false;
+                    for each_clause in &clauses {
+                        if each_clause.iter().any(|literal| assigned.contains(literal)) {
+                            continue;
+                        }
+                        let mut unassigned = each_clause.iter()
+                                                        .filter(|&&l| !assigned.contains(&-l));
+                        match (unassigned.next(), unassigned.next()) {
+                            (None, _) => conflict = true,
+                            (Some(&unit), None) => {
+                                assigned.insert(unit);
+                                if each_clause.len() >= 3 {
+                                    implied.push(unit);
+                                }
+                                changed = true;
+                            },
+                            _ => {},
+                        }
+                        if conflict {
+                            break;
+                        }
+                    }
+                }
+                if !conflict {
+                    for literal in implied {
+                        let mut binary = CNFClause::new();
+                        binary.add(-decision);
+                        binary.add(literal);
+                        binaries.push(binary);
+                    }
+                }
+            }
+        }
+        binaries.into_iter().filter(|binary| self.add_clause(binary.clone())).count()
+    }
+
+    /// Returns the literals that are true in every model of the system, or nothing if it's
+    /// Unsatisfiable. Only the literals of a first model can be forced, and every model found
+    /// while checking one of them rules out the rest it disagrees with, so this takes far fewer
+    /// solves than checking every literal like `dpll::backbone`. Each literal found forced is
+    /// propagated into the system the later checks start from, which has the same models, so a
+    /// literal it leaves as a unit is settled without a solve, and the solves left have less to
+    /// search.
+    ///
+    /// This still copies and solves the system once for every literal that stays a candidate,
+    /// which is quadratic at best, so it's only meant for systems that solve quickly
+    pub fn forced_units(&self) -> BTreeSet<isize> {
+        use dpll::basic_dpll;
+
+        let (clause_type, model) = basic_dpll(self.clone());
+        if clause_type != ClauseType::Satisfiable {
+            return BTreeSet::new();
+        }
+        // A variable the model leaves out can be either way, so it can't be forced
+        let mut candidates = model;
+        let mut forced = BTreeSet::new();
+        let mut settled = self.clone();
+        while let Some(&literal) = candidates.iter().next() {
+            candidates.remove(&literal);
+            let mut system = settled.clone();
+            let is_forced = system.propagate(-literal).is_none() || match basic_dpll(system) {
+                (ClauseType::Satisfiable, other_model) => {
+                    candidates.retain(|candidate| other_model.contains(candidate));
+                    false
+                },
+                _ => true,
+            };
+            if is_forced {
+                forced.insert(literal);
+                settled.propagate(literal);
+            }
+        }
+        forced
+    }
+
+    /// Looks ahead on each variable that isn't a unit: propagates v and ¬v separately, and adds
+    /// the literals both branches force as units, which can find units that propagating or
+    /// probing a single literal can't. A variable whose one branch reaches a contradiction has its
+    /// other literal added instead, and if both do the system is Unsatisfiable, so an empty clause
+    /// is added. This is repeated until nothing new is found. Returns the literals added as units
+    pub fn lookahead_fix(&mut self) -> HashSet<isize> {
+        let mut fixed = HashSet::new();
+        if self.clone().unit_simplify().is_err() {
+            return fixed;
+        }
+        let unit = |literal| {
+            let mut clause = CNFClause::new();
+            clause.add(literal);
+            clause
+        };
+        let propagated = |system: &CNFSystem, literal: isize| {
+            let mut branch = system.clone();
+            branch.add_clause(unit(literal));
+            branch.unit_simplify().ok()
+        };
+        let mut found_any = true;
+        while found_any {
+            found_any = false;
+            let units = self.units();
+            let unit_variables = units.iter().map(|literal| literal.unsigned_abs())
+                                      .collect::<HashSet<_>>();
+            let mut new_units = BTreeSet::new();
+            for variable in self.variables().into_iter().filter(|v| !unit_variables.contains(v)) {
+                let literal = variable as isize;
+                match (propagated(self, literal), propagated(self, -literal)) {
+                    (Some(positive), Some(negative)) => {
+                        new_units.extend(positive.intersection(&negative)
+                                                 .filter(|literal| !units.contains(literal)));
+                    },
+                    (Some(_), None) => { new_units.insert(literal); },
+                    (None, Some(_)) => { new_units.insert(-literal); },
+                    (None, None) => {
+                        self.add_clause(CNFClause::new());
+                        return fixed;
+                    },
+                }
+            }
+            for literal in new_units {
+                found_any = true;
+                fixed.insert(literal);
+                self.add_clause(unit(literal));
+            }
+            if found_any && self.clone().unit_simplify().is_err() {
+                return fixed;
+            }
+        }
+        fixed
+    }
+
+    /// Returns a normalized copy of the system with tautologies and subsumed clauses removed, and
+    /// no comments, so that `a.canonical() == b.canonical()` is a meaningful check. This is a
+    /// structural normalization, not a semantic one: equivalent systems can still have different
+    /// canonical forms, e.g. (a ∨ b)(a ∨ ¬b) and (a)
+    pub fn canonical(&self) -> CNFSystem {
+        let mut clauses = self.clauses.iter()
+                                      .filter(|clause| !clause.is_tautology())
+                                      .collect::<Vec<_>>();
+        // A clause can only be subsumed by one no longer than it, so checking the shorter clauses
+        // first means every clause kept is already known to not be subsumed
+        clauses.sort_by_key(|clause| clause.len());
+        let mut canonical_system = CNFSystem::new(None);
+        let mut kept: Vec<&CNFClause> = vec![];
+        for each_clause in clauses {
+            if !kept.iter().any(|kept_clause| kept_clause.subsumes(each_clause)) {
+                kept.push(each_clause);
+                canonical_system.add_clause(each_clause.clone());
+            }
+        }
+        canonical_system
+    }
+
+    /// Simplifies the system without changing whether it's satisfiable: removes its tautologies,
+    /// propagates its units and then removes every clause subsumed by another. A model of the
+    /// simplified system together with the forced literals is a model of the system. Returns what
+    /// was removed
+    pub fn simplify(&mut self) -> SimplifyReport {
+        let mut report = SimplifyReport {
+            clauses_before: self.len(),
+            variables_before: self.variables().len(),
+            literals_before: self.literal_count(),
+            ..SimplifyReport::default()
+        };
+        report.tautologies_removed = self.remove_tautologies();
+        match self.unit_simplify() {
+            Ok(forced_literals) => {
+                report.forced_literals = forced_literals;
+                let clause_count = self.len();
+                let comments = ::std::mem::take(&mut self.comments);
+                *self = self.canonical();
+                self.comments = comments;
+                report.subsumed_removed = clause_count - self.len();
+            },
+            Err(()) => {
+                report.unsatisfiable = true;
+                self.clauses.clear();
+                self.add_clause(CNFClause::new());
+            },
+        }
+        report.clauses_after = self.len();
+        report.variables_after = self.variables().len();
+        report.literals_after = self.literal_count();
+        report
+    }
+
+    /// Returns the amount of literals over all of the clauses
+    pub fn literal_count(&self) -> usize {
+        self.clauses.iter().map(CNFClause::len).sum()
+    }
+
+    /// Removes every tautological clause, which is always true and so never changes whether the
+    /// system is satisfiable. Returns how many were removed
+    pub fn remove_tautologies(&mut self) -> usize {
+        let clause_count = self.len();
+        self.clauses.retain(|clause| !clause.is_tautology());
+        clause_count - self.len()
+    }
+
+    /// Removes both literals of the variable from every clause, for editing a system rather than
+    /// solving it: unlike eliminating the variable, this changes what the system means. A clause
+    /// that only had literals of the variable is left empty, making the system Unsatisfiable, and
+    /// clauses that become the same are merged. Returns how many literals were removed
+    pub fn delete_variable(&mut self, variable: usize) -> usize {
+        let literal = variable as isize;
+        let mut removed = 0;
+        self.clauses = self.clauses.drain().map(|mut clause| {
+            removed += clause.remove(&literal) as usize + clause.remove(&-literal) as usize;
+            clause
+        }).collect();
+        removed
+    }
+
+    /// Returns true if the model satisfies every clause of the system. See
+    /// `CNFClause::is_satisfied_by`
+    pub fn is_satisfied_by(&self, model: &BTreeSet<isize>) -> bool {
+        self.unsatisfied_clause(model).is_none()
+    }
+
+    /// Returns the least clause, in clause order, that the model doesn't satisfy, or None if it
+    /// satisfies them all
+    pub fn unsatisfied_clause(&self, model: &BTreeSet<isize>) -> Option<&CNFClause> {
+        self.clauses.iter().filter(|clause| !clause.is_satisfied_by(model)).min()
+    }
+
+    /// Returns the system in logic notation, e.g. (x1 ∨ ¬x2) ∧ (x2 ∨ x3), with its clauses in
+    /// order so that the same system always reads the same. An empty system is ⊤
+    pub fn pretty(&self) -> String {
+        if self.is_empty() {
+            return "⊤".to_string();
+        }
+        let mut clauses = self.clauses.iter().collect::<Vec<_>>();
+        clauses.sort();
+        clauses.iter().map(|clause| clause.pretty()).collect::<Vec<_>>().join(" ∧ ")
+    }
+
+    /// Returns the literal of every unit clause in the system
+    pub fn units(&self) -> HashSet<isize> {
+        self.clauses.iter().filter_map(CNFClause::get_unit).collect()
+    }
+
+    /// Returns the pure literals of the system i.e. those whose negation doesn't occur anywhere in
+    /// the system. The system isn't changed; setting every pure literal to true is always safe
+    pub fn pure_literals(&self) -> HashSet<isize> {
+        let literals: HashSet<isize> = self.clauses.iter()
+                                               .flat_map(|clause| clause.iter().cloned())
+                                               .collect();
+        literals.iter().filter(|literal| !literals.contains(&-**literal)).cloned().collect()
+    }
+
+    /// Returns how many clauses contain the positive literal of the variable and how many contain
+    /// the negative one. A tautology containing both counts towards both
+    pub fn occurrences(&self, variable: usize) -> (usize, usize) {
+        let literal = variable as isize;
+        let count = |literal| self.clauses.iter().filter(|clause| clause.contains(literal)).count();
+        (count(literal), count(-literal))
+    }
+
+    /// Returns the literals that assuming `literal` forces true through the binary clauses alone:
+    /// each (x ∨ y) makes ¬x imply y and ¬y imply x, and these implications are followed as far as
+    /// they go. Longer clauses are ignored, so propagation may force more. The literal itself isn't
+    /// included unless it implies itself through a cycle, and the result holds its negation if
+    /// assuming it is a contradiction
+    pub fn implications_of(&self, literal: isize) -> BTreeSet<isize> {
+        let mut implications: HashMap<isize, Vec<isize>> = HashMap::new();
+        for each_clause in self.clauses.iter().filter(|clause| clause.len() == 2) {
+            let literals = each_clause.iter().cloned().collect::<Vec<_>>();
+            implications.entry(-literals[0]).or_default().push(literals[1]);
+            implications.entry(-literals[1]).or_default().push(literals[0]);
+        }
+        let mut implied = BTreeSet::new();
+        let mut to_visit = vec![literal];
+        while let Some(current) = to_visit.pop() {
+            for &next in implications.get(&current).map_or(&[][..], |next| &next[..]) {
+                if implied.insert(next) {
+                    to_visit.push(next);
+                }
+            }
+        }
+        implied
+    }
+
+    /// Returns a literal by the Maximum Occurrences in clauses of Minimum size (MOM) heuristic:
+    /// the variable that occurs most among the shortest non-empty clauses, with the polarity it
+    /// occurs in most there. Ties go to the lowest variable, then to the positive literal. None if
+    /// the system has no literals
+    pub fn mom_literal(&self) -> Option<isize> {
+        let shortest_length = self.clauses.iter()
+                                          .map(CNFClause::len)
+                                          .filter(|&length| length > 0)
+                                          .min()?;
+        // The positive and negative occurrences of each variable in the shortest clauses
+        let mut occurrences: BTreeMap<usize, (usize, usize)> = BTreeMap::new();
+        for each_clause in self.clauses.iter().filter(|clause| clause.len() == shortest_length) {
+            for &literal in each_clause.iter() {
+                let counts = occurrences.entry(literal.unsigned_abs()).or_insert((0, 0));
+                if literal > 0 { counts.0 += 1 } else { counts.1 += 1 }
+            }
+        }
+        // max_by_key() keeps the last of equal elements, so go from the highest variable down
+        let (variable, (positive, negative)) = occurrences.into_iter().rev().max_by_key(
+            |&(_, (positive, negative))| positive + negative)?;
+        let literal = variable as isize;
+        Some(if positive >= negative { literal } else { -literal })
+    }
+
+    /// Splits the system into its clauses of at most two literals, i.e. its units, binary clauses
+    /// and any empty clause, and its longer clauses. Every clause ends up in exactly one of the
+    /// two, so solving them together is solving the system
+    pub fn partition_by_arity(&self) -> (CNFSystem, CNFSystem) {
+        let (short_clauses, long_clauses) = self.clauses.iter()
+                                                        .cloned()
+                                                        .partition(|clause| clause.len() <= 2);
+        (CNFSystem::new(Some(short_clauses)), CNFSystem::new(Some(long_clauses)))
+    }
+
+    /// Returns the negation of the system in CNF, along with the last variable it uses. Each
+    /// clause C of the system gets a new auxiliary variable t, numbered upwards from the
+    /// system's largest variable, which implies every literal of C is false, and at least one
+    /// of these variables has to be true. So an assignment of the system's variables extends to
+    /// a model of the negation exactly when it isn't a model of the system
+    pub fn negate_tseitin(&self) -> (CNFSystem, usize) {
+        let first_auxiliary_variable = self.variables().last().map_or(1, |variable| variable + 1);
+        self.negate_tseitin_from(first_auxiliary_variable)
+    }
+
+    /// negate_tseitin(), numbering the auxiliary variables upwards from `first_auxiliary_variable`
+    /// instead, e.g. to keep them clear of the variables of another system too
+    pub fn negate_tseitin_from(&self, first_auxiliary_variable: usize) -> (CNFSystem, usize) {
+        let mut allocator = VarAllocator::starting_at(first_auxiliary_variable);
+        let negation = self.negate_tseitin_with(&mut allocator);
+        (negation, allocator.next_variable() - 1)
+    }
+
+    /// negate_tseitin(), taking the auxiliary variables from `allocator`. The clauses are
+    /// numbered in order, so the same system always gives the same negation
+    pub fn negate_tseitin_with(&self, allocator: &mut VarAllocator) -> CNFSystem {
+        let mut clauses = self.clauses.iter().collect::<Vec<_>>();
+        clauses.sort();
+        let mut negation = CNFSystem::new(None);
+        let mut some_clause_is_false = CNFClause::new();
+        let auxiliary_variables = allocator.fresh_range(clauses.len());
+        for (each_clause, auxiliary_variable) in clauses.iter().zip(auxiliary_variables) {
+            let auxiliary_literal = auxiliary_variable as isize;
+            for literal in each_clause.iter() {
+                // t → ¬l
+                let mut clause = CNFClause::new();
+                clause.add(-auxiliary_literal);
+                clause.add(-literal);
+                negation.add_clause(clause);
+            }
+            some_clause_is_false.add(auxiliary_literal);
+        }
+        // The negation of an empty system (which is always true) is this empty clause
+        negation.add_clause(some_clause_is_false);
+        negation
+    }
+
+    /// Returns an iterator over the clauses as plain lists of literals, for passing them to code
+    /// that doesn't know about CNFClause. The clauses come in order. Fails without yielding
+    /// anything if any literal doesn't fit in an i32
+    pub fn clauses_as_vecs(&self)
+                           -> Result<impl Iterator<Item = Vec<i32>>, TryFromIntError> {
+        let mut clauses = self.clauses.iter()
+                                      .map(|clause| clause.iter()
+                                                          .map(|&literal| i32::try_from(literal))
+                                                          .collect::<Result<Vec<_>, _>>())
+                                      .collect::<Result<Vec<_>, _>>()?;
+        clauses.sort();
+        Ok(clauses.into_iter())
+    }
+
+    /// Returns every variable that occurs in the system, in either polarity
+    pub fn variables(&self) -> BTreeSet<usize> {
+        self.clauses.iter()
+                    .flat_map(|clause| clause.iter())
+                    .map(|literal| literal.unsigned_abs())
+                    .collect()
+    }
+}
+
+#[test]
+fn test_cnf_system_unit_simplify() {
+    use testutil::system_from;
+
+    // (a)(¬a ∨ b) forces both a and b, which satisfies every clause
+    let mut system = system_from(&[&[1], &[-1, 2]]);
+    let forced: BTreeSet<isize> = vec![1, 2].into_iter().collect();
+    assert_eq!(Ok(forced), system.unit_simplify());
+    assert!(system.is_empty());
+
+    // Clauses that aren't made unit are only reduced
+    let mut system = system_from(&[&[1], &[-1, 2, 3], &[-2, 3, 4]]);
+    let forced: BTreeSet<isize> = vec![1].into_iter().collect();
+    assert_eq!(Ok(forced), system.unit_simplify());
+    assert_eq!(system_from(&[&[2, 3], &[-2, 3, 4]]), system);
+
+    let mut system = system_from(&[&[1], &[-1, 2], &[-2]]);
+    assert_eq!(Err(()), system.unit_simplify());
+    assert_eq!(Err(()), system_from(&[&[1, 2], &[]]).unit_simplify());
+}
+
+#[test]
+fn test_cnf_system_initial_phases() {
+    use testutil::system_from;
+
+    // 1 is mostly positive, 2 is tied and 3 is only ever negative
+    let system = system_from(&[&[1, 2], &[1, -2, -3], &[-1, -3], &[1, 4]]);
+    let phases = system.initial_phases();
+    assert_eq!(4, phases.len());
+    assert_eq!(Some(&true), phases.get(&1));
+    assert_eq!(Some(&false), phases.get(&2));
+    assert_eq!(Some(&false), phases.get(&3));
+    assert_eq!(Some(&true), phases.get(&4));
+}
+
+#[test]
+fn test_cnf_system_connected_components() {
+    use testutil::system_from;
+
+    let first_half = system_from(&[&[1, 2], &[-2, 3], &[-1, -3]]);
+    let second_half = system_from(&[&[4, -5], &[5, 6, 7], &[-7]]);
+    let mut system = first_half.clone();
+    for each_clause in &second_half.clauses {
+        system.add_clause(each_clause.clone());
+    }
+    let components = system.connected_components();
+    assert_eq!(2, components.len());
+    assert!(components.contains(&first_half));
+    assert!(components.contains(&second_half));
+
+    // A clause sharing a variable with each half joins them back together
+    system.add_clause(system_from(&[&[3, 4]]).clauses.into_iter().next().unwrap());
+    assert_eq!(vec![system.clone()], system.connected_components());
+
+    assert!(CNFSystem::new(None).connected_components().is_empty());
+}
+
+#[test]
+fn test_cnf_system_metrics() {
+    use testutil::system_from;
+
+    let system = system_from(&[&[1], &[-1, 2], &[-2, -3, -4], &[2, -3, 4]]);
+    let histogram: BTreeMap<usize, usize> = vec![(1, 1), (2, 1), (3, 2)].into_iter().collect();
+    assert_eq!(histogram, system.clause_length_histogram());
+    assert!(!system.is_horn());
+    assert!(!system.is_2sat());
+
+    let system = system_from(&[&[1], &[-1, 2], &[-2, -3]]);
+    assert!(system.is_horn());
+    assert!(system.is_2sat());
+
+    let empty_system = CNFSystem::new(None);
+    assert!(empty_system.clause_length_histogram().is_empty());
+    assert!(empty_system.is_horn());
+    assert!(empty_system.is_2sat());
+}
+
+#[test]
+fn test_cnf_system_resolution_closure() {
+    use testutil::system_from;
+
+    // (a)(¬a) resolves straight to the empty clause
+    let mut system = system_from(&[&[1], &[-1]]);
+    assert_eq!(1, system.resolution_closure(0));
+    assert!(system.clauses.contains(&CNFClause::new()));
+
+    // (1 ∨ 2)(¬1 ∨ 3) only resolves to (2 ∨ 3), which is too long for a bound of 1
+    let mut system = system_from(&[&[1, 2], &[-1, 3]]);
+    assert_eq!(0, system.resolution_closure(1));
+    assert_eq!(2, system.len());
+    assert_eq!(1, system.resolution_closure(2));
+    assert_eq!(system_from(&[&[1, 2], &[-1, 3], &[2, 3]]), system);
+
+    // Resolvents of resolvents are added too, and tautologies never are
+    let mut system = system_from(&[&[1, 2], &[-1, 2], &[1, -2], &[-1, -2]]);
+    system.resolution_closure(2);
+    assert!(system.clauses.contains(&CNFClause::new()));
+    assert!(system.clauses.iter().all(|clause| !clause.is_tautology()));
+}
+
+#[test]
+fn test_cnf_system_canonical() {
+    use testutil::system_from;
+
+    // The same clauses added in a different order, with a tautology, a subsumed clause and a
+    // comment mixed in
+    let system = system_from(&[&[1, 2], &[-3], &[2, -1, 4]]);
+    let mut other_system = system_from(&[&[1, -1, 5], &[-3, 1], &[-1, 4, 2], &[-3], &[2, 1]]);
+    other_system.comments.push("generated differently".to_string());
+    assert!(system != other_system);
+    assert_eq!(system.canonical(), other_system.canonical());
+    assert_eq!(system, system.canonical());
+
+    // Equivalent, but not structurally the same
+    assert!(system_from(&[&[1, 2], &[1, -2]]).canonical() != system_from(&[&[1]]).canonical());
+
+    // The empty clause subsumes everything
+    assert_eq!(system_from(&[&[]]), system_from(&[&[1], &[], &[-2, 3]]).canonical());
+}
+
+#[test]
+fn test_cnf_system_add_clause_reporting() {
+    let mut system = CNFSystem::new(None);
+    let clause = CNFClause::from_literals(&[1, -2]).unwrap();
+    assert_eq!(AddOutcome::Added, system.add_clause_reporting(clause.clone()));
+    assert_eq!(AddOutcome::Duplicate, system.add_clause_reporting(clause));
+    let unit = CNFClause::from_literals(&[-3]).unwrap();
+    assert_eq!(AddOutcome::Unit(-3), system.add_clause_reporting(unit.clone()));
+    assert_eq!(AddOutcome::Duplicate, system.add_clause_reporting(unit));
+    assert_eq!(AddOutcome::Empty, system.add_clause_reporting(CNFClause::new()));
+    assert_eq!(3, system.len());
+}
+
+#[test]
+fn test_cnf_system_remove_clause() {
+    use dpll::basic_dpll;
+    use testutil::system_from;
+
+    // (a ∨ b)(¬a)(¬b) is only Unsatisfiable because of (¬b), so retracting it leaves a model
+    let mut system = system_from(&[&[1, 2], &[-1], &[-2]]);
+    assert_eq!(ClauseType::Unsatisfiable, basic_dpll(system.clone()).0);
+    let clause = CNFClause::from_literals(&[-2]).unwrap();
+    assert!(system.remove_clause(&clause));
+    assert!(!system.remove_clause(&clause));
+    let (clause_type, model) = basic_dpll(system.clone());
+    assert_eq!(ClauseType::Satisfiable, clause_type);
+    assert!(system.is_satisfied_by(&model));
+}
+
+#[test]
+fn test_cnf_system_units() {
+    use testutil::system_from;
+
+    let system = system_from(&[&[3], &[-1], &[1, 2, -3]]);
+    let units = system.units();
+    assert_eq!(2, units.len());
+    assert!(units.contains(&3));
+    assert!(units.contains(&-1));
+    assert_eq!(None, CNFClause::from_literals(&[1, 2, -3]).unwrap().get_unit());
+    assert_eq!(None, CNFClause::new().get_unit());
+}
+
+#[test]
+fn test_cnf_system_pure_literals() {
+    use testutil::system_from;
+
+    // 1 only occurs positively and 3 only negatively, while 2 occurs in both polarities
+    let system = system_from(&[&[1, 2], &[-2, -3], &[1, -3, 2]]);
+    let pure_literals = system.pure_literals();
+    assert_eq!(2, pure_literals.len());
+    assert!(pure_literals.contains(&1));
+    assert!(pure_literals.contains(&-3));
+    assert_eq!(3, system.len());
+
+    assert!(system_from(&[&[1], &[-1]]).pure_literals().is_empty());
+}
+
+#[test]
+fn test_cnf_system_occurrences() {
+    use testutil::system_from;
+
+    let system = system_from(&[&[1, 2], &[1, -3], &[-1, 3], &[2, 3]]);
+    assert_eq!((2, 1), system.occurrences(1));
+    assert_eq!((2, 0), system.occurrences(2));
+    assert_eq!((0, 0), system.occurrences(4));
+    assert_eq!((1, 1), system_from(&[&[1, -1, 2]]).occurrences(1));
+}
+
+#[test]
+fn test_cnf_system_implications_of() {
+    use testutil::system_from;
+
+    let set = |literals: &[isize]| literals.iter().cloned().collect::<BTreeSet<_>>();
+    // (¬a ∨ b)(¬b ∨ c), so a implies b and c, and ¬c implies ¬b and ¬a. Propagating a would
+    // also force d through the ternary clause, but that's ignored
+    let system = system_from(&[&[-1, 2], &[-2, 3], &[-1, -3, 4]]);
+    assert_eq!(set(&[2, 3]), system.implications_of(1));
+    assert_eq!(set(&[]), system.implications_of(3));
+    assert_eq!(set(&[-2, -1]), system.implications_of(-3));
+
+    // a implies both b and ¬b, and so ¬a, so it can never be true
+    let system = system_from(&[&[-1, 2], &[-2, -1]]);
+    assert_eq!(set(&[-2, -1, 2]), system.implications_of(1));
+}
+
+#[test]
+fn test_cnf_system_mom_literal() {
+    use testutil::system_from;
+
+    // 3 occurs in every binary clause, and negatively in most of them
+    let system = system_from(&[&[1, 2, 4], &[-3, 2], &[-3, 1], &[3, 4], &[1, 2, 5, 6]]);
+    assert_eq!(Some(-3), system.mom_literal());
+
+    // A unit clause is the shortest there is
+    let system = system_from(&[&[1, 2], &[1, -2], &[4]]);
+    assert_eq!(Some(4), system.mom_literal());
+
+    // Ties go to the lowest variable, and empty clauses don't count
+    let system = system_from(&[&[3, 2], &[-3, -2], &[]]);
+    assert_eq!(Some(2), system.mom_literal());
+    assert_eq!(None, system_from(&[&[]]).mom_literal());
+    assert_eq!(None, CNFSystem::new(None).mom_literal());
+}
+
+#[test]
+fn test_cnf_system_simplify() {
+    use testutil::system_from;
+
+    // (1 ∨ ¬1 ∨ 2) is a tautology, the unit 4 satisfies (4 ∨ 5) and shortens (¬4 ∨ 1 ∨ 2 ∨ 3) to
+    // (1 ∨ 2 ∨ 3), which (1 ∨ 2) then subsumes, as it does (1 ∨ 2 ∨ 6)
+    let mut system = system_from(&[&[1, -1, 2], &[4], &[4, 5], &[-4, 1, 2, 3], &[1, 2], &[1, 2, 6],
+                                   &[-2, 3]]);
+    let report = system.simplify();
+    assert_eq!(system_from(&[&[1, 2], &[-2, 3]]), system);
+    assert_eq!((7, 2), (report.clauses_before, report.clauses_after));
+    assert_eq!((6, 3), (report.variables_before, report.variables_after));
+    assert_eq!((17, 4), (report.literals_before, report.literals_after));
+    assert_eq!(1, report.tautologies_removed);
+    assert_eq!(2, report.subsumed_removed);
+    assert_eq!(vec![4].into_iter().collect::<BTreeSet<_>>(), report.forced_literals);
+    assert!(!report.unsatisfiable);
+
+    let mut system = system_from(&[&[1], &[-1, 2], &[-2, -1], &[3, 4]]);
+    assert!(system.simplify().unsatisfiable);
+    assert_eq!(system_from(&[&[]]), system);
+}
+
+#[test]
+fn test_cnf_system_is_satisfied_by() {
+    use testutil::system_from;
+
+    let model = |literals: &[isize]| literals.iter().cloned().collect::<BTreeSet<isize>>();
+    let system = system_from(&[&[1, -2], &[2, 3], &[-1, -3], &[2, 4]]);
+    // A partial model is fine as long as it satisfies every clause
+    assert!(system.is_satisfied_by(&model(&[1, 2, -3])));
+    assert_eq!(None, system.unsatisfied_clause(&model(&[1, 2, -3])));
+    // Leaving 3 unassigned doesn't satisfy (¬1 ∨ ¬3)
+    assert!(!system.is_satisfied_by(&model(&[1, 2])));
+    assert_eq!(Some(&CNFClause::from_literals(&[-1, -3]).unwrap()),
+               system.unsatisfied_clause(&model(&[1, 2])));
+    assert_eq!(Some(&CNFClause::from_literals(&[2, 3]).unwrap()),
+               system.unsatisfied_clause(&model(&[-1, -2])));
+    assert!(CNFSystem::new(None).is_satisfied_by(&model(&[])));
+}
+
+#[test]
+fn test_cnf_system_pretty() {
+    use testutil::system_from;
+
+    assert_eq!("(x1 ∨ ¬x2)", CNFClause::from_literals(&[1, -2]).unwrap().pretty());
+    assert_eq!("(x3 ∨ ¬x3)", CNFClause::from_literals(&[-3, 3]).unwrap().pretty());
+    assert_eq!("(x1 ∨ ¬x2) ∧ (x2 ∨ x3)", system_from(&[&[2, 3], &[1, -2]]).pretty());
+    assert_eq!("⊥ ∧ (x4)", system_from(&[&[4], &[]]).pretty());
+    assert_eq!("⊤", CNFSystem::new(None).pretty());
+}
+
+#[test]
+fn test_cnf_system_delete_variable() {
+    use testutil::system_from;
+
+    // (-2 ∨ 3) and (2 ∨ 3) both become (3)
+    let mut system = system_from(&[&[1, -2], &[-1, 2, 4], &[2], &[3, 4], &[-2, 3], &[2, 3]]);
+    assert_eq!(5, system.delete_variable(2));
+    assert_eq!(system_from(&[&[1], &[-1, 4], &[], &[3, 4], &[3]]), system);
+    assert_eq!((0, 0), system.occurrences(2));
+
+    assert_eq!(0, system.delete_variable(7));
+    assert_eq!(5, system.len());
+}
+
+#[test]
+fn test_cnf_system_partition_by_arity() {
+    use testutil::system_from;
+
+    let system = system_from(&[&[1], &[1, -2], &[-1, 3], &[1, 2, 3], &[-1, -2, -3, 4]]);
+    let (short, long) = system.partition_by_arity();
+    assert_eq!(system_from(&[&[1], &[1, -2], &[-1, 3]]), short);
+    assert_eq!(system_from(&[&[1, 2, 3], &[-1, -2, -3, 4]]), long);
+    assert!(short.clauses.is_disjoint(&long.clauses));
+    assert_eq!(system.clauses, short.clauses.union(&long.clauses).cloned().collect());
+
+    let (short, long) = CNFSystem::new(None).partition_by_arity();
+    assert!(short.is_empty() && long.is_empty());
+}
+
+#[test]
+fn test_cnf_system_hyper_binary_resolve() {
+    use testutil::system_from;
+
+    // 1 implies 2 and 3, which together imply 4 through the ternary clause, so 1 implies 4
+    let mut system = system_from(&[&[-1, 2], &[-1, 3], &[-2, -3, 4]]);
+    assert_eq!(1, system.hyper_binary_resolve());
+    assert_eq!(system_from(&[&[-1, 2], &[-1, 3], &[-2, -3, 4], &[-1, 4]]), system);
+    assert_eq!(0, system.hyper_binary_resolve());
+
+    // Implications through binary clauses alone give no shortcuts
+    let mut system = system_from(&[&[-1, 2], &[-2, 3], &[-3, 4]]);
+    assert_eq!(0, system.hyper_binary_resolve());
+
+    // Setting 1 forces 2 both through (¬3 ∨ 2) and through the ternary clause. The clauses are
+    // visited in order, so the same shortcuts come out of every copy, however it was hashed
+    let clauses: &[&[isize]] = &[&[-1, 3], &[-3, 2], &[-1, -3, 2], &[3, 4, 5], &[-4, -5, 1]];
+    let mut first_system = system_from(clauses);
+    let count = first_system.hyper_binary_resolve();
+    for _ in 0..20 {
+        let mut system = system_from(clauses);
+        let mut clone = system.clone();
+        assert_eq!(count, system.hyper_binary_resolve());
+        assert_eq!(count, clone.hyper_binary_resolve());
+        assert_eq!(first_system, system);
+        assert_eq!(first_system, clone);
+    }
+}
+
+#[test]
+fn test_cnf_system_negate_tseitin() {
+    use brute_force::Assignments;
+    use dpll::basic_dpll;
+    use testutil::{satisfies, system_from};
+
+    let system = system_from(&[&[1, 2], &[-1, 3], &[-2, -3]]);
+    let (negation, last_variable) = system.negate_tseitin();
+    assert_eq!(6, last_variable);
+    assert_eq!(last_variable, *negation.variables().iter().max().unwrap());
+
+    // Fixing the system's variables, the negation is Satisfiable exactly when the system isn't
+    for assignment in Assignments::new(&system.variables()) {
+        let mut negation = negation.clone();
+        for &literal in &assignment {
+            negation.add_clause(CNFClause::from_literals(&[literal]).unwrap());
+        }
+        let negation_is_satisfiable = basic_dpll(negation).0 == ClauseType::Satisfiable;
+        assert_eq!(!satisfies(&system, &assignment), negation_is_satisfiable);
+    }
+
+    // A system and its negation can never both be true
+    let mut both = system.clone();
+    for each_clause in negation.clauses {
+        both.add_clause(each_clause);
+    }
+    assert_eq!(ClauseType::Unsatisfiable, basic_dpll(both).0);
+
+    // The negation of the empty system, which is always true, is never true
+    assert_eq!((system_from(&[&[]]), 0), CNFSystem::new(None).negate_tseitin());
+}
+
+#[test]
+fn test_cnf_system_clauses_as_vecs() {
+    use testutil::system_from;
+
+    let system = system_from(&[&[3, -1], &[2], &[-2, 1, 4]]);
+    let clauses = system.clauses_as_vecs().unwrap().collect::<Vec<_>>();
+    assert_eq!(vec![vec![-2, 1, 4], vec![-1, 3], vec![2]], clauses);
+    let rebuilt = clauses.iter()
+                         .map(|clause| clause.iter().map(|&literal| literal as isize).collect())
+                         .collect::<Vec<Vec<isize>>>();
+    let rebuilt = system_from(&rebuilt.iter().map(|clause| &clause[..]).collect::<Vec<_>>());
+    assert_eq!(system, rebuilt);
+
+    let too_large = i32::MAX as isize + 1;
+    assert!(system_from(&[&[1], &[too_large]]).clauses_as_vecs().is_err());
+}
+
+#[test]
+fn test_cnf_system_lookahead_fix() {
+    use testutil::system_from;
+
+    // 1 forces 2 and so 3, and ¬1 forces 4 and so 3, so 3 is forced. Neither ¬3 nor anything
+    // else propagates to a contradiction, so probing single literals can't find it
+    let mut system = system_from(&[&[-1, 2], &[-1, -2, 3], &[1, 4], &[1, -4, 3]]);
+    let mut probed = system.clone();
+    probed.add_clause(CNFClause::from_literals(&[-3]).unwrap());
+    assert!(probed.unit_simplify().is_ok());
+    assert_eq!(vec![3].into_iter().collect::<HashSet<_>>(), system.lookahead_fix());
+    assert!(system.clauses.contains(&CNFClause::from_literals(&[3]).unwrap()));
+    assert!(system.lookahead_fix().is_empty());
+
+    // A branch that contradicts itself forces the other one, and if both do there's no model
+    let mut system = system_from(&[&[-1, 2], &[-1, -2], &[1, 3, 5]]);
+    assert_eq!(vec![-1].into_iter().collect::<HashSet<_>>(), system.lookahead_fix());
+    let mut system = system_from(&[&[-1, 2], &[-1, -2], &[1, 3], &[1, -3]]);
+    system.lookahead_fix();
+    assert!(system.clauses.contains(&CNFClause::new()));
+}
+
+#[test]
+fn test_cnf_system_forced_units() {
+    use dpll::backbone;
+    use testutil::{XorShift, random_system, system_from};
+
+    // 1 is a unit and forces 2, and ¬3 is forced by 2 ∧ (¬2 ∨ ¬3), but 4 can be either
+    let system = system_from(&[&[1], &[-1, 2], &[-2, -3], &[3, 4, 2], &[-4, 2]]);
+    let expected = [1, 2, -3].iter().cloned().collect::<BTreeSet<isize>>();
+    assert_eq!(expected, system.forced_units());
+    assert_eq!(expected, backbone(&system));
+
+    let mut rng = XorShift::new(147);
+    for _ in 0..50 {
+        let system = random_system(&mut rng, 8, 30, 3);
+        assert_eq!(backbone(&system), system.forced_units());
+    }
+    assert!(system_from(&[&[1], &[-1]]).forced_units().is_empty());
 }
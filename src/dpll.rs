@@ -1,72 +1,781 @@
 use cnf_system::{CNFClause, CNFSystem, ClauseType};
-use std::collections::{BTreeSet, HashSet};
-use std::sync::mpsc;
+use search_tree::SearchTree;
+use var_map::{VarAllocator, VarMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
-/// Applies unit propagation of a literal l to a system.
-///     If a clause contains: l, then remove that entire clause
-///     If a clause contains: not(l), then remove not(l) from the clause but keep the others
-/// Returns (true, new_units) if successful, (false, _) if a set contradicts another
+/// A flag shared between threads that a caller can set to stop an in-progress solve early, in
+/// which case the solver returns Unknown. Clones of a token all share the same flag
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    // The flags of the tokens this one is a child of
+    parents: Vec<Arc<AtomicBool>>,
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Returns a new token that is cancelled whenever this one is, but that can also be cancelled
+    /// on its own without cancelling this one
+    pub fn child(&self) -> CancellationToken {
+        let mut parents = self.parents.clone();
+        parents.push(self.cancelled.clone());
+        CancellationToken{ cancelled: Arc::new(AtomicBool::new(false)), parents }
+    }
+
+    /// Asks every solve using this token to stop as soon as it next checks it
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+            || self.parents.iter().any(|parent| parent.load(Ordering::SeqCst))
+    }
+}
+
+/// Counts of the work done by a solve
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SolverStats {
+    /// Literals branched on
+    pub decisions: usize,
+    /// Literals set by unit propagation or pure literal elimination
+    pub propagations: usize,
+    /// Branches that ended with an empty clause
+    pub conflicts: usize,
+    /// The most decisions that were in force at once in any solve, i.e. the deepest any search
+    /// went
+    pub max_decision_level: usize,
+}
+
+/// The outcome of a solve, along with how much work it took
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolveResult {
+    pub clause_type: ClauseType,
+    /// A model of the system if it's Satisfiable, otherwise meaningless
+    pub model: BTreeSet<isize>,
+    /// Literals branched on before the verdict was reached
+    pub decisions: usize,
+    pub propagations: usize,
+    pub conflicts: usize,
+    /// The deepest this solve went, i.e. the most decisions in force at once. It's 0 if no
+    /// decision was needed
+    pub max_decision_level: usize,
+    pub time: Duration,
+    /// Why the system was decided without any search, if it was. See `trivial_check`
+    pub trivial_reason: Option<TrivialReason>,
+}
+
+impl SolveResult {
+    /// Wraps up a solve that started at `started_at`, when the config's stats were `started_stats`
+    fn finish((clause_type, model): (ClauseType, BTreeSet<isize>), config: &SolverConfig,
+              started_stats: SolverStats, started_at: Instant) -> SolveResult {
+        let stats = config.stats.snapshot();
+        #[cfg(feature = "log")]
+        debug!("solve finished: {:?} after {} decisions and {} conflicts", clause_type,
+               stats.decisions - started_stats.decisions,
+               stats.conflicts - started_stats.conflicts);
+        SolveResult {
+            clause_type,
+            model,
+            decisions: stats.decisions - started_stats.decisions,
+            propagations: stats.propagations - started_stats.propagations,
+            conflicts: stats.conflicts - started_stats.conflicts,
+            max_decision_level: config.stats.solve_max_decision_level.load(Ordering::Relaxed),
+            time: started_at.elapsed(),
+            trivial_reason: None,
+        }
+    }
+}
+
+impl From<SolveResult> for (ClauseType, BTreeSet<isize>) {
+    fn from(result: SolveResult) -> (ClauseType, BTreeSet<isize>) {
+        (result.clause_type, result.model)
+    }
+}
+
+/// Why a system could be decided without searching it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrivialReason {
+    /// The system has no clauses, so it's Satisfiable
+    NoClauses,
+    /// The system has an empty clause, so it's Unsatisfiable
+    EmptyClause,
+    /// The system has the unit clauses (v) and (¬v) of this variable, so it's Unsatisfiable
+    ConflictingUnits(usize),
+    /// Unit propagation alone either satisfied every clause or emptied one
+    UnitPropagation,
+}
+
+impl fmt::Display for TrivialReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TrivialReason::NoClauses => write!(f, "no clauses"),
+            TrivialReason::EmptyClause => write!(f, "an empty clause"),
+            TrivialReason::ConflictingUnits(variable) => {
+                write!(f, "conflicting units {} and -{}", variable, variable)
+            },
+            TrivialReason::UnitPropagation => write!(f, "unit propagation"),
+        }
+    }
+}
+
+impl TrivialReason {
+    /// Translates a reason found for a system compacted by `var_map` back to the input's
+    /// variables, as `VarMap::expand_model` does for a model
+    pub fn expand(self, var_map: &VarMap) -> TrivialReason {
+        match self {
+            TrivialReason::ConflictingUnits(variable) => {
+                TrivialReason::ConflictingUnits(var_map.to_dimacs(variable as isize).unsigned_abs())
+            },
+            reason => reason,
+        }
+    }
+}
+
+/// Counters that solvers add their work to as they go, shared between threads in the same way as
+/// CancellationToken
+#[derive(Clone, Debug, Default)]
+pub struct SharedStats {
+    decisions: Arc<AtomicUsize>,
+    propagations: Arc<AtomicUsize>,
+    conflicts: Arc<AtomicUsize>,
+    max_decision_level: Arc<AtomicUsize>,
+    // max_decision_level for the solve these stats were made for by for_solve() alone
+    solve_max_decision_level: Arc<AtomicUsize>,
+    // The conflicts of each variable, see conflict_histogram()
+    conflict_variables: Arc<Mutex<HashMap<usize, u64>>>,
+}
+
+impl SharedStats {
+    pub fn new() -> SharedStats {
+        SharedStats::default()
+    }
+
+    /// Returns stats adding up to the same counts as these, but with a max_decision_level of
+    /// their own as well, starting from 0, for a solve to report
+    fn for_solve(&self) -> SharedStats {
+        SharedStats{ solve_max_decision_level: Arc::default(), ..self.clone() }
+    }
+
+    /// Returns the counts so far
+    pub fn snapshot(&self) -> SolverStats {
+        SolverStats {
+            decisions: self.decisions.load(Ordering::Relaxed),
+            propagations: self.propagations.load(Ordering::Relaxed),
+            conflicts: self.conflicts.load(Ordering::Relaxed),
+            max_decision_level: self.max_decision_level.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns how many conflicts each variable has been the cause of so far, i.e. how often
+    /// setting it, by a decision or a propagation, left a clause empty. Variables that never
+    /// caused one are left out, as are conflicts found some other way, e.g. an empty clause in
+    /// the input
+    pub fn conflict_histogram(&self) -> HashMap<usize, u64> {
+        self.conflict_variables.lock().unwrap().clone()
+    }
+}
+
+/// How often, in decisions, progress is printed at verbosity 2
+const PROGRESS_INTERVAL: usize = 10000;
+
+/// Settings shared by the solvers
+#[derive(Clone, Debug, Default)]
+pub struct SolverConfig {
+    /// Checked once per branch of the search, which returns Unknown once it's cancelled
+    pub cancellation: CancellationToken,
+    /// Counts the decisions, propagations and conflicts of every solve using this config
+    pub stats: SharedStats,
+    /// 0 to print nothing while solving, 2 to print a ``c'' line of progress every so often. With
+    /// the "log" feature, progress is logged at the info level instead, whatever the verbosity
+    pub verbosity: u8,
+    /// If set, basic_dpll() gives up and returns Unknown once a solve has had more conflicts
+    /// than this. Unlike a timeout, this stops at the same point on every machine
+    pub conflict_budget: Option<usize>,
+    /// The polarity basic_dpll() first tries each variable with, for the variables it covers.
+    /// The rest use `CNFSystem::initial_phases`. This only changes the order of the search
+    pub phases: HashMap<usize, bool>,
+    /// How the variable to branch on is picked
+    pub heuristic: DecisionHeuristic,
+    /// If set, basic_dpll() traces every branch of its search here: a line with the decision
+    /// when it enters the branch, and another with the verdict when it leaves it, both indented
+    /// by the branch's decision level. Nothing is formatted when this is None
+    pub trace: Option<TraceOutput>,
+}
+
+/// Where `SolverConfig::trace` writes its lines
+#[derive(Clone, Debug)]
+pub enum TraceOutput {
+    Stderr,
+    /// Collects the lines instead, e.g. to check them in a test
+    Lines(Arc<Mutex<Vec<String>>>),
+}
+
+/// The ways the solvers can pick the variable to branch on next
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DecisionHeuristic {
+    /// The lowest variable left in the system
+    #[default]
+    LowestVariable,
+    /// The variable that occurs most in the shortest clauses. See `CNFSystem::mom_literal`
+    Mom,
+    /// The lowest variable of the longest clause, the opposite of Mom's focus on short clauses.
+    /// Of the longest clauses, the least in clause order is picked
+    LongestClause,
+}
+
+impl SolverConfig {
+    /// Sets `phases` so that each variable of the model is first tried with its value there,
+    /// e.g. to start from the solution of a similar system. Like `phases`, this only changes
+    /// the order of the search, never its result
+    pub fn set_initial_assignment(&mut self, model: &BTreeSet<isize>) {
+        self.phases.extend(model.iter().map(|&literal| (literal.unsigned_abs(), literal > 0)));
+    }
+
+    /// Returns a copy of the config to run a solve with, so its SolveResult reports the deepest
+    /// decision level of that solve alone
+    fn for_solve(&self) -> SolverConfig {
+        SolverConfig{ stats: self.stats.for_solve(), ..self.clone() }
+    }
+
+    /// Records a decision that brings the search to decision level `level`
+    fn record_decision(&self, level: usize) {
+        self.stats.max_decision_level.fetch_max(level, Ordering::Relaxed);
+        self.stats.solve_max_decision_level.fetch_max(level, Ordering::Relaxed);
+        let decisions = self.stats.decisions.fetch_add(1, Ordering::Relaxed) + 1;
+        if decisions.is_multiple_of(PROGRESS_INTERVAL) {
+            self.report_progress();
+        }
+    }
+
+    /// Traces entering (`verdict` is None) or leaving the branch that decides `literal` at
+    /// decision level `level`
+    fn trace_branch(&self, level: usize, literal: isize, verdict: Option<&ClauseType>) {
+        let output = match self.trace {
+            Some(ref output) => output,
+            None             => return,
+        };
+        let indent = "  ".repeat(level.saturating_sub(1));
+        let line = match verdict {
+            Some(verdict) => format!("{}{} -> {:?}", indent, literal, verdict),
+            None          => format!("{}{}", indent, literal),
+        };
+        match *output {
+            TraceOutput::Stderr           => eprintln!("{}", line),
+            TraceOutput::Lines(ref lines) => lines.lock().unwrap().push(line),
+        }
+    }
+
+    #[cfg(not(feature = "log"))]
+    fn report_progress(&self) {
+        if self.verbosity >= 2 {
+            let stats = self.stats.snapshot();
+            println!("c progress: decisions={} propagations={} conflicts={}",
+                     stats.decisions, stats.propagations, stats.conflicts);
+        }
+    }
+
+    #[cfg(feature = "log")]
+    fn report_progress(&self) {
+        let stats = self.stats.snapshot();
+        info!("progress: decisions={} propagations={} conflicts={}",
+              stats.decisions, stats.propagations, stats.conflicts);
+    }
+
+    fn record_propagation(&self) {
+        self.stats.propagations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a conflict, caused by setting `variable` if there is one
+    fn record_conflict(&self, variable: Option<usize>) {
+        self.stats.conflicts.fetch_add(1, Ordering::Relaxed);
+        if let Some(variable) = variable {
+            *self.stats.conflict_variables.lock().unwrap().entry(variable).or_insert(0) += 1;
+        }
+    }
+}
+
+/// Applies unit propagation of a literal l to a system. See `CNFSystem::propagate`
 pub fn concurrent_dpll_propagate(system: &mut CNFSystem, literal: isize)
                                  -> Option<HashSet<isize>> {
-    let mut new_units = HashSet::new();
-    let mut clauses_to_remove: Vec<CNFClause> = vec![];
-    let mut clauses_to_reduce: Vec<CNFClause> = vec![];
-
-    for each_clause in system.clauses.iter().cloned() {
-        if each_clause.contains(literal) {
-            clauses_to_remove.push(each_clause);
-        } else if each_clause.contains(-literal) {
-            clauses_to_reduce.push(each_clause);
-        }
-    }
-
-    for each_clause in clauses_to_remove {
-        system.remove_clause(&each_clause);
-    }
-
-    for mut each_clause in clauses_to_reduce {
-        // Have to remove and then add because it's a hash
-        // Check if successful because it could have been removed by the clauses_to_remove vector
-        if system.remove_clause(&each_clause) {
-            each_clause.remove(&-literal);
-            match each_clause.len() {
-                0 => { return None; },
-                1 => {
-                    new_units.insert(each_clause.iter()          // get literals iterator
-                                                .next().unwrap() // get first literal
-                                                .clone()         // clone it and insert it
-                                    );
-                },
+    system.propagate(literal)
+}
+
+/// Returns true if the system has a clause with no literals, which can never be satisfied
+fn contains_empty_clause(system: &CNFSystem) -> bool {
+    system.clauses.iter().any(CNFClause::is_empty)
+}
+
+/// The order literals are picked in when there's a choice: by variable, then positive before
+/// negative. The sets of clauses and literals picked from don't iterate in any defined order, so
+/// picking the first literal of the set would make the search differ from run to run
+fn selection_key(literal: &isize) -> (usize, bool) {
+    (literal.unsigned_abs(), *literal < 0)
+}
+
+/// Returns the literal of the system to branch on, picked by the heuristic. Only a system without
+/// any literals left, i.e. an empty one once empty clauses have been ruled out, has none
+fn get_branch_literal(system: &CNFSystem, heuristic: DecisionHeuristic) -> Option<isize> {
+    match heuristic {
+        DecisionHeuristic::LowestVariable => {
+            system.clauses.iter().flat_map(|clause| clause.iter()).cloned()
+                                 .min_by_key(selection_key)
+        },
+        DecisionHeuristic::Mom => system.mom_literal(),
+        DecisionHeuristic::LongestClause => {
+            let longest_clause = system.clauses.iter().max_by(|a, b| {
+                a.len().cmp(&b.len()).then_with(|| b.cmp(a))
+            })?;
+            longest_clause.iter().cloned().min_by_key(selection_key)
+        },
+    }
+}
+
+/// Returns the literal of a unit clause in the system, if there is one
+pub fn basic_dpll_get_unit_literal(system: &CNFSystem) -> Option<isize> {
+    system.clauses.iter().filter_map(CNFClause::get_unit).min_by_key(selection_key)
+}
+
+/// Returns a pure literal of the system i.e. a literal whose negation doesn't occur anywhere in
+/// the system, if there is one
+pub fn basic_dpll_get_pure_literal(system: &CNFSystem) -> Option<isize> {
+    system.pure_literals().into_iter().min_by_key(selection_key)
+}
+
+/// Why a solve stopped without a verdict, where the solvers would otherwise return Unknown
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolveError {
+    /// The config's cancellation token was cancelled
+    Interrupted,
+    /// The solve had more conflicts than the config's conflict budget
+    BudgetExceeded,
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SolveError::Interrupted => write!(f, "the solve was cancelled"),
+            SolveError::BudgetExceeded => write!(f, "the solve ran out of conflicts"),
+        }
+    }
+}
+
+impl ::std::error::Error for SolveError {}
+
+/// Turns a result that is Unknown because of the config into the error saying why
+fn stopped_result(result: SolveResult, config: &SolverConfig) -> Result<SolveResult, SolveError> {
+    if result.clause_type != ClauseType::Unknown {
+        Ok(result)
+    } else if config.conflict_budget.is_some_and(|budget| result.conflicts > budget) {
+        Err(SolveError::BudgetExceeded)
+    } else {
+        Err(SolveError::Interrupted)
+    }
+}
+
+/// Takes in a system (tautologies are allowed, but can be optimised out when parsed), and
+/// return if it's Satisfiable or Unsatisfiable using the DPLL algorithm on the current thread.
+/// Each variable branched on is first tried with the polarity given by `initial_phases`
+pub fn basic_dpll(system: CNFSystem) -> (ClauseType, BTreeSet<isize>) {
+    basic_dpll_with_config(system, &SolverConfig::default()).into()
+}
+
+/// basic_dpll(), using the given settings
+pub fn basic_dpll_with_config(system: CNFSystem, config: &SolverConfig) -> SolveResult {
+    solve_unless_trivial(system, config, |system, config| {
+        BasicDpll::new(&system, config, None).search(system, None, None)
+    })
+}
+
+/// How two configs' solves of the same system went. Verdicts that differ mean a bug, while the
+/// decisions, conflicts and time of each show which config suits the system better
+#[derive(Clone, Debug)]
+pub struct Comparison {
+    pub a: SolveResult,
+    pub b: SolveResult,
+}
+
+impl Comparison {
+    /// Returns true if both configs reached the same verdict
+    pub fn verdicts_agree(&self) -> bool {
+        self.a.clause_type == self.b.clause_type
+    }
+}
+
+/// Solves the system with basic_dpll() under each config. See `Comparison`
+pub fn compare_solvers(system: &CNFSystem, a: SolverConfig, b: SolverConfig) -> Comparison {
+    Comparison {
+        a: basic_dpll_with_config(system.clone(), &a),
+        b: basic_dpll_with_config(system.clone(), &b),
+    }
+}
+
+/// Decides the system without searching it if that's possible, i.e. if it has no clauses, has an
+/// empty clause, has conflicting unit clauses, or is decided by unit propagation alone. Returns
+/// the verdict, a model if it's Satisfiable and the reason it was trivial, otherwise None
+pub fn trivial_check(system: &CNFSystem) -> Option<(ClauseType, BTreeSet<isize>, TrivialReason)> {
+    if system.is_empty() {
+        return Some((ClauseType::Satisfiable, BTreeSet::new(), TrivialReason::NoClauses));
+    }
+    if contains_empty_clause(system) {
+        return Some((ClauseType::Unsatisfiable, BTreeSet::new(), TrivialReason::EmptyClause));
+    }
+    let units = system.units();
+    let conflicting_unit = units.iter()
+                                .filter(|&&literal| literal > 0 && units.contains(&-literal))
+                                .min();
+    if let Some(&literal) = conflicting_unit {
+        let reason = TrivialReason::ConflictingUnits(literal.unsigned_abs());
+        return Some((ClauseType::Unsatisfiable, BTreeSet::new(), reason));
+    }
+    match propagate_units(system, units) {
+        None                => {
+            Some((ClauseType::Unsatisfiable, BTreeSet::new(), TrivialReason::UnitPropagation))
+        },
+        Some((model, true)) => {
+            Some((ClauseType::Satisfiable, model, TrivialReason::UnitPropagation))
+        },
+        Some((_, false))    => None,
+    }
+}
+
+/// Propagates the units, and the units that propagating them reveals, until there are none left.
+/// Returns the literals set and whether they satisfy every clause, or None if they contradict
+/// each other or leave a clause empty.
+///
+/// Unlike `CNFSystem::propagate`, this doesn't copy or rescan the system for each literal: the
+/// clauses each literal occurs in are listed once up front, and each clause keeps a count of its
+/// literals that aren't false yet, so setting a literal only looks at the clauses it occurs in
+fn propagate_units(system: &CNFSystem, units: HashSet<isize>)
+                   -> Option<(BTreeSet<isize>, bool)> {
+    let clauses = system.clauses.iter().collect::<Vec<_>>();
+    let mut occurrences: HashMap<isize, Vec<usize>> = HashMap::new();
+    for (index, each_clause) in clauses.iter().enumerate() {
+        for &literal in each_clause.iter() {
+            occurrences.entry(literal).or_default().push(index);
+        }
+    }
+    let mut open_literals = clauses.iter().map(|clause| clause.len()).collect::<Vec<_>>();
+    let mut satisfied = vec![false; clauses.len()];
+    let mut satisfied_count = 0;
+    let mut model = BTreeSet::new();
+    let mut pending_units = units.into_iter().collect::<Vec<_>>();
+    pending_units.sort_by_key(selection_key);
+    while let Some(literal) = pending_units.pop() {
+        if model.contains(&-literal) {
+            return None;
+        }
+        if !model.insert(literal) {
+            continue;
+        }
+        for &index in occurrences.get(&literal).into_iter().flatten() {
+            if !satisfied[index] {
+                satisfied[index] = true;
+                satisfied_count += 1;
+            }
+        }
+        // A tautology with both literals was just satisfied above, so it's never reduced here
+        for &index in occurrences.get(&-literal).into_iter().flatten() {
+            if satisfied[index] {
+                continue;
+            }
+            open_literals[index] -= 1;
+            match open_literals[index] {
+                0 => return None,
+                // No literal of the clause is true, so the one that isn't false is its unit
+                1 => pending_units.extend(clauses[index].iter().find(|open_literal| {
+                    !model.contains(&-**open_literal)
+                })),
                 _ => {},
+            }
+        }
+    }
+    Some((model, satisfied_count == clauses.len()))
+}
+
+/// Runs `solve` on the system, with the config made for this solve, unless trivial_check() can
+/// decide it without a search
+fn solve_unless_trivial<F>(system: CNFSystem, config: &SolverConfig, solve: F) -> SolveResult
+        where F: FnOnce(CNFSystem, &SolverConfig) -> (ClauseType, BTreeSet<isize>) {
+    let config = &config.for_solve();
+    let (started_stats, started_at) = (config.stats.snapshot(), Instant::now());
+    let (result, trivial_reason) = match trivial_check(&system) {
+        Some((clause_type, model, reason)) => ((clause_type, model), Some(reason)),
+        None => (solve(system, config), None),
+    };
+    let mut result = SolveResult::finish(result, config, started_stats, started_at);
+    result.trivial_reason = trivial_reason;
+    result
+}
+
+/// basic_dpll_with_config(), also recording every branch of the search in `tree`
+pub fn basic_dpll_recording_tree(system: CNFSystem, config: &SolverConfig, tree: &mut SearchTree)
+                                 -> SolveResult {
+    let config = &config.for_solve();
+    let (started_stats, started_at) = (config.stats.snapshot(), Instant::now());
+    let result = BasicDpll::new(&system, config, Some(tree)).search(system, None, None);
+    SolveResult::finish(result, config, started_stats, started_at)
+}
+
+/// The state of a basic_dpll() solve, shared by every branch of the search
+struct BasicDpll<'a> {
+    config: &'a SolverConfig,
+    phases: HashMap<usize, bool>,
+    tree: Option<&'a mut SearchTree>,
+    // The config's conflict count when this solve started
+    started_conflicts: usize,
+    // The amount of decisions in force at the branch being searched
+    level: usize,
+}
+
+impl<'a> BasicDpll<'a> {
+    fn new(system: &CNFSystem, config: &'a SolverConfig, tree: Option<&'a mut SearchTree>)
+           -> BasicDpll<'a> {
+        let started_conflicts = config.stats.snapshot().conflicts;
+        let mut phases = system.initial_phases();
+        phases.extend(config.phases.iter().map(|(&variable, &phase)| (variable, phase)));
+        BasicDpll{ config, phases, tree, started_conflicts, level: 0 }
+    }
+
+    /// Returns true if the search should stop without a verdict
+    fn is_stopped(&self) -> bool {
+        let over_budget = self.config.conflict_budget.is_some_and(|budget| {
+            self.config.stats.snapshot().conflicts - self.started_conflicts > budget
+        });
+        over_budget || self.config.cancellation.is_cancelled()
+    }
+
+    /// Searches the branch reached by deciding `decision` (None at the root), which branches off
+    /// from the branch at tree node `parent`
+    fn search(&mut self, mut system: CNFSystem, decision: Option<isize>, parent: Option<usize>)
+              -> (ClauseType, BTreeSet<isize>) {
+        let node = self.tree.as_mut().map(|tree| tree.add_node(parent, decision));
+        if let Some(literal) = decision {
+            self.config.trace_branch(self.level, literal, None);
+        }
+        let mut interpretation: BTreeSet<isize> = BTreeSet::new();
+        let result = match self.simplify(&mut system, decision, node, &mut interpretation) {
+            Ok(branch_literal) => self.branch(system, branch_literal, node, interpretation),
+            Err(clause_type)   => (clause_type, interpretation),
+        };
+        if let Some(literal) = decision {
+            self.config.trace_branch(self.level, literal, Some(&result.0));
+        }
+        result
+    }
+
+    /// Sets the decision, if there is one, and then propagates units and pure literals until it
+    /// can't anymore, adding every literal set to the interpretation. Returns the literal to
+    /// branch on first, or the verdict if the branch ended without needing one
+    fn simplify(&mut self, system: &mut CNFSystem, decision: Option<isize>, node: Option<usize>,
+                interpretation: &mut BTreeSet<isize>) -> Result<isize, ClauseType> {
+        if self.is_stopped() {
+            return Err(self.leaf(node, ClauseType::Unknown));
+        }
+        if let Some(literal) = decision {
+            self.config.record_decision(self.level);
+            #[cfg(feature = "log")]
+            trace!("decided {} at level {}", literal, self.level);
+            interpretation.insert(literal);
+            if system.propagate(literal).is_none() {
+                return Err(self.conflict(node, Some(literal)));
+            }
+        }
+        if contains_empty_clause(system) {
+            return Err(self.conflict(node, None));
+        }
+
+        // Propagate units and pure literals until you can't propagate anymore
+        loop {
+            if system.is_empty() {
+                return Err(self.leaf(node, ClauseType::Satisfiable));
+            }
+            let literal = match basic_dpll_get_unit_literal(system)
+                                    .or_else(|| basic_dpll_get_pure_literal(system)) {
+                Some(literal) => literal,
+                None          => break,
             };
-            system.add_clause(each_clause);
+            if let (Some(tree), Some(node)) = (self.tree.as_mut(), node) {
+                tree.add_propagation(node, literal);
+            }
+            self.config.record_propagation();
+            if system.propagate(literal).is_none() {
+                return Err(self.conflict(node, Some(literal)));
+            }
+            interpretation.insert(literal);
+        }
+
+        // Now, pick some variable to try with its phase first
+        let some_variable = match get_branch_literal(system, self.config.heuristic) {
+            Some(literal) => literal.abs(),
+            None          => return Err(self.leaf(node, ClauseType::Satisfiable)),
+        };
+        match self.phases.get(&some_variable.unsigned_abs()) {
+            Some(&true) => Ok(some_variable),
+            _           => Ok(-some_variable),
+        }
+    }
+
+    /// Works out if the system is satisfiable with `branch_literal` set, or else with its
+    /// negation set
+    fn branch(&mut self, system: CNFSystem, branch_literal: isize, node: Option<usize>,
+              mut interpretation: BTreeSet<isize>) -> (ClauseType, BTreeSet<isize>) {
+        for &each_literal in &[branch_literal, -branch_literal] {
+            self.level += 1;
+            let branch_result = self.search(system.clone(), Some(each_literal), node);
+            self.level -= 1;
+            match branch_result {
+                (ClauseType::Unsatisfiable, _) => {},
+                (ClauseType::Satisfiable, branch_interpretation) => {
+                    interpretation.extend(branch_interpretation);
+                    debug_assert!(is_consistent(&interpretation));
+                    return (ClauseType::Satisfiable, interpretation);
+                },
+                stopped_result => return stopped_result,
+            }
+        }
+        (ClauseType::Unsatisfiable, interpretation)
+    }
+
+    /// Ends the search of a branch without branching any further
+    fn leaf(&mut self, node: Option<usize>, clause_type: ClauseType) -> ClauseType {
+        if let (Some(tree), Some(node)) = (self.tree.as_mut(), node) {
+            tree.set_result(node, clause_type.clone());
+        }
+        clause_type
+    }
+
+    /// Ends the search of a branch that has reached an empty clause, by setting `literal` if
+    /// there is one
+    fn conflict(&mut self, node: Option<usize>, literal: Option<isize>) -> ClauseType {
+        self.config.record_conflict(literal.map(isize::unsigned_abs));
+        self.leaf(node, ClauseType::Unsatisfiable)
+    }
+
+    /// Returns a copy of this search's state that doesn't record a tree, for searching another
+    /// branch alongside this one
+    #[cfg(feature = "rayon")]
+    fn fork(&self) -> BasicDpll<'a> {
+        BasicDpll {
+            config: self.config,
+            phases: self.phases.clone(),
+            tree: None,
+            started_conflicts: self.started_conflicts,
+            level: self.level,
+        }
+    }
+}
+
+/// basic_dpll_with_config(), returning why the solve stopped instead of a result that's Unknown
+pub fn try_basic_dpll(system: CNFSystem, config: &SolverConfig) -> Result<SolveResult, SolveError> {
+    stopped_result(basic_dpll_with_config(system, config), config)
+}
+
+/// basic_dpll(), giving up with Unknown once it has had more than `max_conflicts` conflicts
+pub fn solve_with_conflict_budget(system: CNFSystem, max_conflicts: usize) -> SolveResult {
+    let config = SolverConfig{ conflict_budget: Some(max_conflicts), ..SolverConfig::default() };
+    basic_dpll_with_config(system, &config)
+}
+
+/// Solves each connected component of the system with basic_dpll() on its own, which is much
+/// quicker than solving the whole system when it falls apart into independent pieces. The system
+/// is Unsatisfiable if any of its components is, otherwise the models of the components together
+/// make a model of the system
+pub fn component_dpll(system: CNFSystem) -> (ClauseType, BTreeSet<isize>) {
+    let mut interpretation = BTreeSet::new();
+    for each_component in system.connected_components() {
+        match basic_dpll(each_component) {
+            (ClauseType::Satisfiable, component_interpretation) => {
+                interpretation.extend(component_interpretation);
+            },
+            (clause_type, _) => return (clause_type, BTreeSet::new()),
         }
     }
-    Some(new_units)
+    (ClauseType::Satisfiable, interpretation)
 }
 
-/// Takes in a system (without any tautologies, as they can be optimised out when parsed), and
-/// return if it's Satisfiable or Unsatisfiable using a concurrent version of the DPLL algorithm.
-/// Assumes that there's at least one clause in the system
-pub fn concurrent_dpll(mut system: CNFSystem, units: HashSet<isize>, thread_count: isize)
+/// Takes in a system (tautologies are allowed, but can be optimised out when parsed), and
+/// return if it's Satisfiable or Unsatisfiable using a concurrent version of the DPLL algorithm:
+/// both polarities of each branch variable are searched at once on threads of their own, and a
+/// model found by the positive branch stops the negative one. The positive branch's result is
+/// always preferred, so the model found doesn't depend on timing. Each split uses up 2 threads,
+/// and once fewer than 2 are left for a branch, it's searched with basic_dpll() on the current
+/// thread, so a `thread_count` of 1 or less spawns no threads at all. Only the top levels are
+/// split, however the search goes below them, so `cube_and_conquer` usually keeps the threads
+/// busier.
+pub fn concurrent_dpll(system: CNFSystem, units: HashSet<isize>, thread_count: isize)
                       -> (ClauseType, BTreeSet<isize>) {
+    concurrent_dpll_with_config(system, units, thread_count, &SolverConfig::default()).into()
+}
+
+/// concurrent_dpll(), using the given settings
+pub fn concurrent_dpll_with_config(system: CNFSystem, units: HashSet<isize>,
+                                   thread_count: isize, config: &SolverConfig) -> SolveResult {
+    let config = &config.for_solve();
+    let (started_stats, started_at) = (config.stats.snapshot(), Instant::now());
+    let result = concurrent_dpll_search(system, units, thread_count, 0, config);
+    SolveResult::finish(result, config, started_stats, started_at)
+}
+
+/// Searches the branch reached by propagating `units` at decision level `level`, splitting the
+/// rest of the search between up to `thread_count` threads
+fn concurrent_dpll_search(mut system: CNFSystem, units: HashSet<isize>, thread_count: isize,
+                          level: usize, config: &SolverConfig) -> (ClauseType, BTreeSet<isize>) {
+    if thread_count <= 1 {
+        // With no threads to split the search between, spawning them is only overhead
+        for each_unit_literal in units {
+            let mut unit = CNFClause::new();
+            unit.add(each_unit_literal);
+            system.add_clause(unit);
+        }
+        let mut search = BasicDpll::new(&system, config, None);
+        search.level = level;
+        return search.search(system, None, None);
+    }
     let mut interpretation: BTreeSet<isize> = BTreeSet::new();
+    if config.cancellation.is_cancelled() {
+        return (ClauseType::Unknown, interpretation);
+    }
+    if system.is_empty() && units.is_empty() {
+        return (ClauseType::Satisfiable, interpretation);
+    }
+    if contains_empty_clause(&system) {
+        config.record_conflict(None);
+        return (ClauseType::Unsatisfiable, interpretation);
+    }
     let mut current_units = units;
 
     // Propagate units until you can't propagate anymore
-    while current_units.len() > 0 {
+    while !current_units.is_empty() {
         // The new units revealed by previous unit propagation
         let mut revealed_units = HashSet::new();
-        for each_unit_literal in current_units {
-            match concurrent_dpll_propagate(&mut system, each_unit_literal) {
-                None            => { return (ClauseType::Unsatisfiable, interpretation); },
+        let mut sorted_units = current_units.into_iter().collect::<Vec<_>>();
+        sorted_units.sort_by_key(selection_key);
+        // Every unit is set even once the system is empty, as a unit given by the caller needn't
+        // occur in the system, and can still contradict another one
+        for each_unit_literal in sorted_units {
+            config.record_propagation();
+            let propagated = if interpretation.contains(&-each_unit_literal) {
+                None
+            } else {
+                system.propagate(each_unit_literal)
+            };
+            match propagated {
+                None            => {
+                    config.record_conflict(Some(each_unit_literal.unsigned_abs()));
+                    return (ClauseType::Unsatisfiable, interpretation);
+                },
                 Some(new_units) => {
                     revealed_units.extend(new_units);
                     interpretation.insert(each_unit_literal);
-                    if system.len() == 0 {
-                        return (ClauseType::Satisfiable, interpretation);
-                    }
                 }
             }
         }
@@ -76,9 +785,10 @@ pub fn concurrent_dpll(mut system: CNFSystem, units: HashSet<isize>, thread_coun
     // Now, pick a new random variable and work out if the system is satisfiable with variable and
     // not(variable).
 
-    // Get the first arbitrary literal in the first arbitrary clause (this assumes that
-    // there's at least one clause in the set)
-    let some_literal = system.clauses.iter().next().unwrap().iter().next().unwrap().clone();
+    let some_literal = match get_branch_literal(&system, config.heuristic) {
+        Some(literal) => literal,
+        None          => return (ClauseType::Satisfiable, interpretation),
+    };
 
     // Create two new units hashes to send to the next instances of concurrent_dpll
     let mut positive_clause = HashSet::new();
@@ -89,47 +799,1018 @@ pub fn concurrent_dpll(mut system: CNFSystem, units: HashSet<isize>, thread_coun
     // Create a new system for the new branch
     let system2 = system.clone();
 
-    // Create a channel to send messages between the new threads
-    let (sender, receiver) = mpsc::channel();
-    let sender1 = sender.clone();
-    let sender2 = sender.clone();
-
-    // Spawn threads for each system. We can call unwrap() on the join() methods because DPLL is
-    // sound and the only way for this unwrap to panic is for the spawned concurrent_dpll() to
-    // panic
-    if thread_count >= 2 {
-        thread::spawn(move || {
-            sender1.send(concurrent_dpll(system, positive_clause, thread_count - 2)).unwrap();
-        }).join().unwrap();
-        thread::spawn(move || {
-            let system = system2;
-            sender2.send(concurrent_dpll(system, negative_clause, thread_count - 2)).unwrap();
-        }).join().unwrap();
-    } else {
-        thread::spawn(move || {
-            sender1.send(concurrent_dpll(system, positive_clause, 0)).unwrap();
-        }).join().unwrap();
-        sender2.send(concurrent_dpll(system2, negative_clause, 0)).unwrap();
-    }
-
-    // Now, wait for one (or both) of the threads to come back with a result
-    let mut thread_result_iterator = receiver.iter();
-    match thread_result_iterator.next().unwrap() {
-        (ClauseType::Unsatisfiable, _) => {
-            // Wait for other result
-            match thread_result_iterator.next().expect("sent two messages but only received one") {
-                (ClauseType::Unsatisfiable, new_interpretation) => {
-                    (ClauseType::Unsatisfiable, new_interpretation)
+    // Spawn a thread for each system. The branches get a token of their own, so that the one
+    // that decides the result can stop the other without cancelling the caller's solve
+    config.record_decision(level + 1);
+    config.record_decision(level + 1);
+    let mut positive_config = config.clone();
+    positive_config.cancellation = config.cancellation.child();
+    let negative_config = positive_config.clone();
+    let branches = positive_config.cancellation.clone();
+    let positive_thread = thread::spawn(move || {
+        concurrent_dpll_search(system, positive_clause, thread_count - 2, level + 1,
+                               &positive_config)
+    });
+    let negative_thread = thread::spawn(move || {
+        concurrent_dpll_search(system2, negative_clause, thread_count - 2, level + 1,
+                               &negative_config)
+    });
+
+    // Both branches search at once, but the positive one's result is always preferred, so that
+    // which model is found doesn't depend on which thread gets there first. We can call unwrap()
+    // on the join() methods because the only way for them to panic is for the search to panic
+    let positive_result = positive_thread.join().unwrap();
+    if positive_result.0 != ClauseType::Unsatisfiable {
+        // A model, or a stopped search, decides the result without the other branch
+        branches.cancel();
+    }
+    let negative_result = negative_thread.join().unwrap();
+    let (clause_type, new_interpretation) = match positive_result {
+        (ClauseType::Unsatisfiable, _) => negative_result,
+        positive_result                => positive_result,
+    };
+    match clause_type {
+        ClauseType::Unknown       => (ClauseType::Unknown, BTreeSet::new()),
+        ClauseType::Unsatisfiable => (ClauseType::Unsatisfiable, new_interpretation),
+        clause_type => {
+            // Only the satisfying branch's interpretation is merged in, never the unsatisfiable
+            // one's
+            interpretation.extend(new_interpretation);
+            debug_assert!(is_consistent(&interpretation));
+            (clause_type, interpretation)
+        },
+    }
+}
+
+/// Takes in a system (tautologies are allowed), and returns if it's Satisfiable or Unsatisfiable
+/// by cube and conquer: the search space is first split into up to `cube_count` cubes, i.e.
+/// partial assignments of the variables that occur most, and then `thread_count` threads each
+/// take the next cube left and solve the system under it with basic_dpll(). The first model found
+/// by any thread is the result, and the system is Unsatisfiable if it is under every cube.
+pub fn cube_and_conquer(system: CNFSystem, cube_count: usize, thread_count: usize)
+                        -> SolveResult {
+    cube_and_conquer_with_config(system, cube_count, thread_count, &SolverConfig::default())
+}
+
+/// cube_and_conquer(), using the given settings
+pub fn cube_and_conquer_with_config(system: CNFSystem, cube_count: usize, thread_count: usize,
+                                    config: &SolverConfig) -> SolveResult {
+    solve_unless_trivial(system, config, |system, config| {
+        conquer(&system, &cubes(&system, cube_count), thread_count, config)
+    })
+}
+
+/// cube_and_conquer_with_config(), returning why the solve stopped instead of a result that's
+/// Unknown
+pub fn try_cube_and_conquer(system: CNFSystem, cube_count: usize, thread_count: usize,
+                            config: &SolverConfig) -> Result<SolveResult, SolveError> {
+    stopped_result(cube_and_conquer_with_config(system, cube_count, thread_count, config), config)
+}
+
+/// Returns the cubes to split the system's search space into: every assignment of the
+/// ⌈log2(cube_count)⌉ variables with the most occurrences, so every total assignment extends
+/// exactly one cube. Variables with the same amount of occurrences are taken in order, so the
+/// same system always gives the same cubes
+fn cubes(system: &CNFSystem, cube_count: usize) -> Vec<Vec<isize>> {
+    let mut occurrences: HashMap<usize, usize> = HashMap::new();
+    for literal in system.clauses.iter().flat_map(|clause| clause.iter()) {
+        *occurrences.entry(literal.unsigned_abs()).or_insert(0) += 1;
+    }
+    let mut variables = occurrences.into_iter().collect::<Vec<_>>();
+    variables.sort_by(|&(a, a_count), &(b, b_count)| b_count.cmp(&a_count).then(a.cmp(&b)));
+    let mut split_count = 0;
+    while 1 << split_count < cube_count && split_count < variables.len() {
+        split_count += 1;
+    }
+    (0..1usize << split_count).map(|cube| {
+        variables[..split_count].iter().enumerate().map(|(bit, &(variable, _))| {
+            if cube & (1 << bit) == 0 { variable as isize } else { -(variable as isize) }
+        }).collect()
+    }).collect()
+}
+
+/// Solves the system under each cube on a pool of threads
+fn conquer(system: &CNFSystem, cubes: &[Vec<isize>], thread_count: usize, config: &SolverConfig)
+           -> (ClauseType, BTreeSet<isize>) {
+    // Finding a model stops every other thread, but cancelling the solve as a whole still works
+    let worker_config = SolverConfig{ cancellation: config.cancellation.child(), ..config.clone() };
+    let next_cube = AtomicUsize::new(0);
+    let model: Mutex<Option<BTreeSet<isize>>> = Mutex::new(None);
+    let stopped = AtomicBool::new(false);
+    thread::scope(|scope| {
+        for _ in 0..thread_count.clamp(1, cubes.len()) {
+            scope.spawn(|| {
+                while let Some(cube) = cubes.get(next_cube.fetch_add(1, Ordering::SeqCst)) {
+                    match solve_cube(system, cube, &worker_config) {
+                        (ClauseType::Unsatisfiable, _) => {},
+                        (ClauseType::Satisfiable, cube_model) => {
+                            let mut model = model.lock().unwrap();
+                            if model.is_none() {
+                                *model = Some(cube_model);
+                                worker_config.cancellation.cancel();
+                            }
+                            return;
+                        },
+                        _ => {
+                            stopped.store(true, Ordering::SeqCst);
+                            return;
+                        },
+                    }
+                }
+            });
+        }
+    });
+    match model.into_inner().unwrap() {
+        Some(model) => {
+            debug_assert!(is_consistent(&model));
+            (ClauseType::Satisfiable, model)
+        },
+        None if stopped.into_inner() => (ClauseType::Unknown, BTreeSet::new()),
+        None => (ClauseType::Unsatisfiable, BTreeSet::new()),
+    }
+}
+
+/// Solves the system with every literal of the cube set
+fn solve_cube(system: &CNFSystem, cube: &[isize], config: &SolverConfig)
+              -> (ClauseType, BTreeSet<isize>) {
+    let mut system = system.clone();
+    for (index, &literal) in cube.iter().enumerate() {
+        config.record_decision(index + 1);
+        if system.propagate(literal).is_none() {
+            config.record_conflict(Some(literal.unsigned_abs()));
+            return (ClauseType::Unsatisfiable, BTreeSet::new());
+        }
+    }
+    // The cube's literals are decisions the search goes on from
+    let mut search = BasicDpll::new(&system, config, None);
+    search.level = cube.len();
+    let (clause_type, mut model) = search.search(system, None, None);
+    model.extend(cube.iter().cloned());
+    (clause_type, model)
+}
+
+/// How many more levels of the search rayon_dpll() splits into tasks than it would need to give
+/// every thread one, so that threads which finish their tasks early can steal more
+#[cfg(feature = "rayon")]
+const RAYON_EXTRA_SPLITS: usize = 4;
+
+/// Takes in a system (tautologies are allowed), and returns if it's Satisfiable or
+/// Unsatisfiable by basic_dpll() on a pool of `thread_count` rayon threads: the top levels of the
+/// search explore both polarities of their branch variable at once with `rayon::join`, and
+/// deeper levels are searched on whichever thread got there. The first model found stops the
+/// rest of the search. The verdict is always the same, but which model is found can depend on
+/// which thread gets there first
+#[cfg(feature = "rayon")]
+pub fn rayon_dpll(system: CNFSystem, thread_count: usize, config: &SolverConfig) -> SolveResult {
+    solve_unless_trivial(system, config, |system, config| {
+        let mut split_depth = RAYON_EXTRA_SPLITS;
+        while 1 << (split_depth - RAYON_EXTRA_SPLITS) < thread_count {
+            split_depth += 1;
+        }
+        // Finding a model stops every other branch, but cancelling the solve as a whole still
+        // works
+        let worker_config = SolverConfig{ cancellation: config.cancellation.child(),
+                                          ..config.clone() };
+        let search = BasicDpll::new(&system, &worker_config, None);
+        match ::rayon::ThreadPoolBuilder::new().num_threads(thread_count).build() {
+            Ok(pool) => pool.install(|| rayon_search(search, system, None, split_depth)),
+            Err(_)   => rayon_search(search, system, None, 0),
+        }
+    })
+}
+
+/// Searches the branch reached by deciding `decision`, splitting it into a rayon task for each
+/// polarity of its branch variable until the search is `split_depth` levels deep
+#[cfg(feature = "rayon")]
+fn rayon_search(mut search: BasicDpll, mut system: CNFSystem, decision: Option<isize>,
+                split_depth: usize) -> (ClauseType, BTreeSet<isize>) {
+    let mut interpretation = BTreeSet::new();
+    let result = match search.simplify(&mut system, decision, None, &mut interpretation) {
+        Err(clause_type) => (clause_type, interpretation),
+        Ok(branch_literal) if search.level >= split_depth => {
+            search.branch(system, branch_literal, None, interpretation)
+        },
+        Ok(branch_literal) => {
+            let search_branch = |literal| {
+                let mut branch_search = search.fork();
+                branch_search.level += 1;
+                rayon_search(branch_search, system.clone(), Some(literal), split_depth)
+            };
+            let results = ::rayon::join(|| search_branch(branch_literal),
+                                        || search_branch(-branch_literal));
+            match results {
+                ((ClauseType::Satisfiable, branch_interpretation), _) |
+                (_, (ClauseType::Satisfiable, branch_interpretation)) => {
+                    interpretation.extend(branch_interpretation);
+                    debug_assert!(is_consistent(&interpretation));
+                    (ClauseType::Satisfiable, interpretation)
                 },
-                (clause_type, new_interpretation) => {
-                    interpretation.extend(new_interpretation);
-                    (clause_type, interpretation)
+                ((ClauseType::Unsatisfiable, _), (ClauseType::Unsatisfiable, _)) => {
+                    (ClauseType::Unsatisfiable, interpretation)
                 },
+                _ => (ClauseType::Unknown, BTreeSet::new()),
             }
         },
-        (clause_type, new_interpretation) => {
-            interpretation.extend(new_interpretation);
-            (clause_type, interpretation)
+    };
+    if result.0 == ClauseType::Satisfiable {
+        search.config.cancellation.cancel();
+    }
+    result
+}
+
+/// Returns true if no variable appears in the interpretation as both a literal and its negation
+pub fn is_consistent(interpretation: &BTreeSet<isize>) -> bool {
+    interpretation.iter().all(|literal| !interpretation.contains(&-literal))
+}
+
+/// Returns the variables that are set one way in model `a` and the other way in model `b`.
+/// Variables that only one of the models sets aren't counted
+pub fn model_diff(a: &BTreeSet<isize>, b: &BTreeSet<isize>) -> BTreeSet<usize> {
+    a.iter().filter(|literal| b.contains(&-**literal))
+            .map(|literal| literal.unsigned_abs())
+            .collect()
+}
+
+/// Returns None if the system is Satisfiable with every literal of `assumptions` fixed as true.
+/// Otherwise returns a core of the assumptions: some of them, in their original order, that
+/// can't all be true together with the system, such that leaving out any one of them makes it
+/// Satisfiable again. The core is empty if the system is Unsatisfiable on its own
+pub fn assumption_core(system: &CNFSystem, assumptions: &[isize]) -> Option<Vec<isize>> {
+    let is_satisfiable_under = |assumptions: &[isize]| {
+        let mut system = system.clone();
+        for &literal in assumptions {
+            let mut unit = CNFClause::new();
+            unit.add(literal);
+            system.add_clause(unit);
+        }
+        basic_dpll(system).0 == ClauseType::Satisfiable
+    };
+    if is_satisfiable_under(assumptions) {
+        return None;
+    }
+    // Leave out each assumption in turn, for good if the rest are still Unsatisfiable
+    let mut core = assumptions.to_vec();
+    let mut index = 0;
+    while index < core.len() {
+        let mut without = core.clone();
+        without.remove(index);
+        if is_satisfiable_under(&without) {
+            index += 1;
+        } else {
+            core = without;
+        }
+    }
+    Some(core)
+}
+
+/// Returns true if the literal is true in every model of the system, i.e. if system ∧ ¬literal is
+/// Unsatisfiable. Every interpretation is a model of an empty system, so it entails nothing
+pub fn entails(system: &CNFSystem, literal: isize) -> bool {
+    if system.is_empty() {
+        return false;
+    }
+    let mut system = system.clone();
+    match system.propagate(-literal) {
+        None    => true,
+        Some(_) => basic_dpll(system).0 == ClauseType::Unsatisfiable,
+    }
+}
+
+/// Returns an interpretation that makes the target clause false but every other clause of the
+/// system true, or None if there's none. The target doesn't have to be a clause of the system
+pub fn falsify_clause(system: &CNFSystem, target: &CNFClause) -> Option<BTreeSet<isize>> {
+    let mut rest = system.clone();
+    rest.remove_clause(target);
+    for &literal in target.iter() {
+        let mut unit = CNFClause::new();
+        unit.add(-literal);
+        rest.add_clause(unit);
+    }
+    match basic_dpll(rest) {
+        (ClauseType::Satisfiable, mut interpretation) => {
+            interpretation.extend(target.iter().map(|literal| -literal));
+            Some(interpretation)
+        },
+        _ => None,
+    }
+}
+
+/// Returns the backbone of the system, i.e. every literal that is true in all of its models, by
+/// checking whether the system entails each literal in turn. Empty if the system is
+/// Unsatisfiable. See `CNFSystem::forced_units`, which finds the same literals much quicker
+pub fn backbone(system: &CNFSystem) -> BTreeSet<isize> {
+    if basic_dpll(system.clone()).0 != ClauseType::Satisfiable {
+        return BTreeSet::new();
+    }
+    system.variables().into_iter()
+                      .flat_map(|variable| vec![variable as isize, -(variable as isize)])
+                      .filter(|&literal| entails(system, literal))
+                      .collect()
+}
+
+/// Returns true if both systems have exactly the same models over their variables, i.e. if
+/// a ∧ ¬b and b ∧ ¬a are both Unsatisfiable
+pub fn equivalent(a: &CNFSystem, b: &CNFSystem) -> bool {
+    // Keep the auxiliary variables of the negations clear of every variable of a and b
+    let mut allocator = VarAllocator::above([a, b]);
+    let mut implies = |a: &CNFSystem, b: &CNFSystem| {
+        let mut system = a.clone();
+        for each_clause in b.negate_tseitin_with(&mut allocator).clauses {
+            system.add_clause(each_clause);
+        }
+        basic_dpll(system).0 == ClauseType::Unsatisfiable
+    };
+    implies(a, b) && implies(b, a)
+}
+
+#[test]
+fn test_entails() {
+    use testutil::system_from;
+
+    // 1 is forced by its unit, and forces 2 in turn, but 3 can be either true or false
+    let system = system_from(&[&[1], &[-1, 2], &[2, 3]]);
+    assert!(entails(&system, 1));
+    assert!(entails(&system, 2));
+    assert!(!entails(&system, -2));
+    assert!(!entails(&system, 3));
+    assert!(!entails(&system, -3));
+
+    assert!(!entails(&CNFSystem::new(None), 1));
+}
+
+#[test]
+fn test_falsify_clause() {
+    use testutil::{satisfies, system_from};
+
+    let system = system_from(&[&[1, 2], &[-1, 3], &[-2, 3], &[3, 4]]);
+    let target = CNFClause::from_literals(&[1, 2]).unwrap();
+    let interpretation = falsify_clause(&system, &target).unwrap();
+    assert!(interpretation.contains(&-1) && interpretation.contains(&-2));
+    let mut rest = system.clone();
+    rest.remove_clause(&target);
+    assert!(satisfies(&rest, &interpretation));
+
+    // Falsifying (¬1 ∨ 3) and (¬2 ∨ 3) means making 3 false, but (3 ∨ 4) can still be satisfied
+    let target = CNFClause::from_literals(&[-1, 3]).unwrap();
+    assert!(falsify_clause(&system, &target).unwrap().contains(&4));
+    let system = system_from(&[&[1, 2], &[-1, 3], &[-2, 3], &[3]]);
+    assert_eq!(None, falsify_clause(&system, &target));
+    assert_eq!(None, falsify_clause(&system, &CNFClause::from_literals(&[1, -1]).unwrap()));
+}
+
+#[test]
+fn test_dpll_empty_clause() {
+    use testutil::system_from;
+
+    for system in &[system_from(&[&[]]), system_from(&[&[1, 2], &[], &[-1]])] {
+        assert_eq!(ClauseType::Unsatisfiable, basic_dpll(system.clone()).0);
+        assert_eq!(ClauseType::Unsatisfiable, concurrent_dpll(system.clone(), HashSet::new(), 4).0);
+        let units = vec![-1].into_iter().collect();
+        assert_eq!(ClauseType::Unsatisfiable, concurrent_dpll(system.clone(), units, 0).0);
+    }
+}
+
+#[test]
+fn test_dpll_empty_system() {
+    let empty = || CNFSystem::new(None);
+    let satisfiable = (ClauseType::Satisfiable, BTreeSet::new());
+    assert_eq!(satisfiable, basic_dpll(empty()));
+    for &thread_count in &[4, 1, 0] {
+        assert_eq!(satisfiable, concurrent_dpll(empty(), HashSet::new(), thread_count));
+    }
+    assert_eq!(satisfiable, component_dpll(empty()));
+    let mut tree = SearchTree::new();
+    let result = basic_dpll_recording_tree(empty(), &SolverConfig::default(), &mut tree);
+    assert_eq!(satisfiable, result.into());
+    assert_eq!(0, tree.decision_count());
+
+    // Units given to concurrent_dpll() are still set when there are no clauses
+    let units = vec![3].into_iter().collect();
+    assert_eq!((ClauseType::Satisfiable, vec![3].into_iter().collect()),
+               concurrent_dpll(empty(), units, 4));
+}
+
+#[test]
+fn test_dpll_tautologies() {
+    use testutil::{satisfies, system_from, XorShift, random_system};
+
+    // Propagating either literal of (1 ∨ ¬1 ∨ 2) removes it rather than reducing it
+    let mut system = system_from(&[&[1, -1, 2], &[-2, 3]]);
+    assert_eq!(Some(HashSet::new()), system.clone().propagate(-1));
+    system.propagate(1);
+    assert_eq!(system_from(&[&[-2, 3]]), system);
+
+    let mut rng = XorShift::new(199);
+    for _ in 0..40 {
+        let system = random_system(&mut rng, 6, 28, 3);
+        let mut tautological_system = system.clone();
+        for _ in 0..3 {
+            let variable = (rng.below(6) + 1) as isize;
+            let other_variable = (rng.below(6) + 1) as isize;
+            tautological_system.add_clause(CNFClause::from_literals(&[variable, -variable,
+                                                                      -other_variable]).unwrap());
+        }
+        let expected = basic_dpll(system).0;
+        let results = vec![basic_dpll(tautological_system.clone()),
+                           component_dpll(tautological_system.clone()),
+                           concurrent_dpll(tautological_system.clone(), HashSet::new(), 4),
+                           cube_and_conquer(tautological_system.clone(), 4, 2).into()];
+        for (clause_type, model) in results {
+            assert_eq!(expected, clause_type);
+            if clause_type == ClauseType::Satisfiable {
+                assert!(satisfies(&tautological_system, &model));
+            }
+        }
+    }
+    let (clause_type, model) = basic_dpll(system_from(&[&[1, -1]]));
+    assert_eq!(ClauseType::Satisfiable, clause_type);
+    assert!(is_consistent(&model));
+}
+
+#[test]
+fn test_is_consistent() {
+    let interpretation: BTreeSet<isize> = vec![1, -2, 3].into_iter().collect();
+    assert!(is_consistent(&interpretation));
+    assert!(is_consistent(&BTreeSet::new()));
+    let interpretation: BTreeSet<isize> = vec![1, -2, 2].into_iter().collect();
+    assert!(!is_consistent(&interpretation));
+}
+
+#[test]
+fn test_dpll_interpretations_are_consistent() {
+    use testutil::{XorShift, random_system, satisfies, system_from};
+
+    // Whichever polarity of a variable is tried first, one of the two branches fails and the
+    // other has to assign the same variable the other way
+    let systems = vec![system_from(&[&[-1, 2], &[-1, -2], &[1, 3], &[1, -3, 4]]),
+                       system_from(&[&[1, 2], &[1, -2], &[-1, 3], &[-3, 4, 5], &[-4, -5]])];
+    let mut rng = XorShift::new(106);
+    let random_systems = (0..100).map(|_| random_system(&mut rng, 8, 30, 3)).collect::<Vec<_>>();
+    for system in systems.into_iter().chain(random_systems) {
+        let results = [basic_dpll(system.clone()),
+                       concurrent_dpll(system.clone(), HashSet::new(), 4)];
+        for (clause_type, interpretation) in &results {
+            assert!(is_consistent(interpretation));
+            if *clause_type == ClauseType::Satisfiable {
+                assert!(satisfies(&system, interpretation));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_cube_and_conquer() {
+    use testutil::{XorShift, pigeonhole, random_system, satisfies, system_from};
+
+    let mut rng = XorShift::new(134);
+    for _ in 0..100 {
+        let system = random_system(&mut rng, 12, 52, 3);
+        let expected = basic_dpll(system.clone()).0;
+        let result = cube_and_conquer(system.clone(), 8, 4);
+        assert_eq!(expected, result.clause_type);
+        if result.clause_type == ClauseType::Satisfiable {
+            assert!(is_consistent(&result.model));
+            assert!(satisfies(&system, &result.model));
+        }
+    }
+    assert_eq!(ClauseType::Unsatisfiable, cube_and_conquer(pigeonhole(4), 16, 3).clause_type);
+
+    // More cubes or threads than the system has room for are fine
+    let system = system_from(&[&[1, 2]]);
+    let result = cube_and_conquer(system.clone(), 64, 64);
+    assert_eq!(ClauseType::Satisfiable, result.clause_type);
+    assert!(satisfies(&system, &result.model));
+    assert_eq!(ClauseType::Unsatisfiable, cube_and_conquer(system_from(&[&[]]), 4, 2).clause_type);
+    assert_eq!(ClauseType::Satisfiable, cube_and_conquer(CNFSystem::new(None), 4, 2).clause_type);
+
+    // The split covers every assignment of the chosen variables exactly once
+    let cubes = cubes(&system_from(&[&[1, 2, 3], &[-1, 2], &[1, -3, 4]]), 3);
+    assert_eq!(vec![vec![1, 2], vec![-1, 2], vec![1, -2], vec![-1, -2]], cubes);
+}
+
+#[test]
+fn test_model_diff() {
+    let a = [1, -2, 3, -4, 5].iter().cloned().collect::<BTreeSet<isize>>();
+    let b = [1, 2, 3, 4, 5].iter().cloned().collect::<BTreeSet<isize>>();
+    assert_eq!([2, 4].iter().cloned().collect::<BTreeSet<usize>>(), model_diff(&a, &b));
+    assert_eq!(model_diff(&a, &b), model_diff(&b, &a));
+    assert!(model_diff(&a, &a).is_empty());
+
+    let partial = [-1, 6].iter().cloned().collect::<BTreeSet<isize>>();
+    assert_eq!([1].iter().cloned().collect::<BTreeSet<usize>>(), model_diff(&a, &partial));
+}
+
+#[test]
+fn test_dpll_cancellation() {
+    use std::time::{Duration, Instant};
+    use testutil::{pigeonhole, system_from};
+
+    // A token that's already cancelled stops both solvers before they do anything
+    let config = SolverConfig::default();
+    config.cancellation.cancel();
+    let system = system_from(&[&[1, 2], &[-1, 2]]);
+    assert_eq!(ClauseType::Unknown, basic_dpll_with_config(system.clone(), &config).clause_type);
+    assert_eq!(ClauseType::Unknown,
+               concurrent_dpll_with_config(system.clone(), HashSet::new(), 4, &config).clause_type);
+    assert_eq!(ClauseType::Unknown,
+               cube_and_conquer_with_config(system, 4, 2, &config).clause_type);
+
+    // Cancelling a child token doesn't cancel its parent, but cancelling the parent cancels both
+    let token = CancellationToken::new();
+    let child = token.child();
+    let grandchild = child.child();
+    child.cancel();
+    assert!(!token.is_cancelled() && child.is_cancelled() && grandchild.is_cancelled());
+    let child = token.child().child();
+    token.cancel();
+    assert!(child.is_cancelled());
+
+    // Fitting 11 pigeons into 10 holes takes plain DPLL far longer than this test waits
+    let config = SolverConfig::default();
+    let token = config.cancellation.clone();
+    let solve = thread::spawn(move || basic_dpll_with_config(pigeonhole(10), &config).clause_type);
+    thread::sleep(Duration::from_millis(100));
+    let cancelled_at = Instant::now();
+    token.cancel();
+    assert_eq!(ClauseType::Unknown, solve.join().unwrap());
+    assert!(cancelled_at.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn test_dpll_stats() {
+    use testutil::system_from;
+
+    // Both polarities of the first variable have to be tried, and each makes the unit propagated
+    // under it contradict the other clause it's in
+    let system = system_from(&[&[1, 2], &[-1, 2], &[1, -2], &[-1, -2]]);
+    let config = SolverConfig::default();
+    assert_eq!(ClauseType::Unsatisfiable,
+               basic_dpll_with_config(system.clone(), &config).clause_type);
+    let stats = config.stats.snapshot();
+    assert_eq!(2, stats.decisions);
+    assert_eq!(2, stats.conflicts);
+    assert_eq!(2, stats.propagations);
+
+    let config = SolverConfig::default();
+    assert_eq!(ClauseType::Unsatisfiable,
+               concurrent_dpll_with_config(system, HashSet::new(), 4, &config).clause_type);
+    let stats = config.stats.snapshot();
+    assert_eq!(2, stats.decisions);
+    assert_eq!(2, stats.conflicts);
+
+    // Stats are shared by clones of a config, like the cancellation token
+    let config = SolverConfig::default();
+    let clone = config.clone();
+    basic_dpll_with_config(system_from(&[&[1, 2], &[-1, 2]]), &clone);
+    assert_eq!(clone.stats.snapshot(), config.stats.snapshot());
+}
+
+#[test]
+fn test_solve_result() {
+    use testutil::{pigeonhole, system_from};
+
+    // Fitting 4 pigeons into 3 holes can't be decided without branching
+    let config = SolverConfig::default();
+    let result = basic_dpll_with_config(pigeonhole(3), &config);
+    assert_eq!(ClauseType::Unsatisfiable, result.clause_type);
+    assert!(result.decisions > 0);
+    assert!(result.conflicts > 0);
+
+    // The counts are of this solve only, even though the config's stats keep adding up
+    let second_result = basic_dpll_with_config(pigeonhole(3), &config);
+    assert!(second_result.decisions > 0);
+    assert_eq!(result.decisions + second_result.decisions, config.stats.snapshot().decisions);
+
+    // (a)(¬a ∨ b) is decided by propagation alone
+    let result = basic_dpll_with_config(system_from(&[&[1], &[-1, 2]]), &config);
+    assert_eq!(0, result.decisions);
+    let model = result.model.clone();
+    let (clause_type, interpretation): (ClauseType, BTreeSet<isize>) = result.into();
+    assert_eq!(ClauseType::Satisfiable, clause_type);
+    assert_eq!(model, interpretation);
+}
+
+#[test]
+fn test_max_decision_level() {
+    use testutil::{pigeonhole, system_from};
+
+    // Each polarity of one variable is tried, and propagation settles the rest
+    let system = system_from(&[&[1, 2], &[-1, 2], &[1, -2], &[-1, -2]]);
+    assert_eq!(1, basic_dpll_with_config(system.clone(), &SolverConfig::default())
+                      .max_decision_level);
+    assert_eq!(1, concurrent_dpll_with_config(system, HashSet::new(), 4, &SolverConfig::default())
+                      .max_decision_level);
+
+    // Every decision of the search is one level down from where it branched
+    let mut tree = SearchTree::new();
+    let result = basic_dpll_recording_tree(pigeonhole(3), &SolverConfig::default(), &mut tree);
+    let nodes = tree.nodes();
+    let depth = |mut node: usize| {
+        let mut depth = 0;
+        while let Some(parent) = nodes[node].parent {
+            depth += 1;
+            node = parent;
+        }
+        depth
+    };
+    assert_eq!((0..nodes.len()).map(depth).max(), Some(result.max_decision_level));
+    assert!(result.max_decision_level > 1);
+
+    // A system decided by unit propagation alone never gets past level 0
+    let system = system_from(&[&[1], &[-1, 2], &[-2, 3]]);
+    assert_eq!(0, basic_dpll_with_config(system.clone(), &SolverConfig::default())
+                      .max_decision_level);
+    assert_eq!(0, cube_and_conquer(system, 1, 1).max_decision_level);
+
+    // Each solve reports its own depth, while the stats keep the deepest of every solve
+    let config = SolverConfig::default();
+    let deep_result = basic_dpll_with_config(pigeonhole(3), &config);
+    let shallow_result = basic_dpll_with_config(system_from(&[&[1, 2], &[-1, 2]]), &config);
+    assert_eq!(0, shallow_result.max_decision_level);
+    assert_eq!(deep_result.max_decision_level, config.stats.snapshot().max_decision_level);
+    let system = system_from(&[&[1, 2], &[-1, 2], &[1, -2], &[-1, -2]]);
+    let result = concurrent_dpll_with_config(system, HashSet::new(), 4, &config);
+    assert_eq!(1, result.max_decision_level);
+}
+
+#[test]
+fn test_trivial_check() {
+    use testutil::{satisfies, system_from};
+
+    let reason = |system: &CNFSystem| trivial_check(system).map(|(_, _, reason)| reason);
+    assert_eq!(Some(TrivialReason::NoClauses), reason(&CNFSystem::new(None)));
+    assert_eq!(Some(TrivialReason::EmptyClause), reason(&system_from(&[&[1, 2], &[]])));
+    let system = system_from(&[&[1, 2], &[-3], &[2], &[3], &[-2]]);
+    let (clause_type, _, reason) = trivial_check(&system).unwrap();
+    assert_eq!(ClauseType::Unsatisfiable, clause_type);
+    assert_eq!(TrivialReason::ConflictingUnits(2), reason);
+    assert_eq!("conflicting units 2 and -2", reason.to_string());
+    // The variable of a compacted system is translated back to the input's
+    let mut var_map = VarMap::new();
+    let system = var_map.compact(&system_from(&[&[1, 2], &[20], &[-20]]));
+    let (_, _, reason) = trivial_check(&system).unwrap();
+    assert_eq!(TrivialReason::ConflictingUnits(20), reason.expand(&var_map));
+    assert_eq!(TrivialReason::EmptyClause, TrivialReason::EmptyClause.expand(&var_map));
+
+    // Propagation can satisfy every clause or reach a contradiction, or leave clauses to search
+    let system = system_from(&[&[1], &[-1, 2], &[-2, 3, -1]]);
+    let (clause_type, model, reason) = trivial_check(&system).unwrap();
+    assert_eq!((ClauseType::Satisfiable, TrivialReason::UnitPropagation), (clause_type, reason));
+    assert!(satisfies(&system, &model));
+    let system = system_from(&[&[1], &[-1, 2], &[-2, -1]]);
+    assert_eq!(Some((ClauseType::Unsatisfiable, BTreeSet::new(), TrivialReason::UnitPropagation)),
+               trivial_check(&system));
+    assert_eq!(None, trivial_check(&system_from(&[&[1], &[-1, 2, 3]])));
+
+    // A long chain of implications is decided without a single decision
+    let mut chain = system_from(&[&[1], &[-5000, -4999]]);
+    for variable in 1..5000 {
+        chain.add_clause(CNFClause::from_literals(&[-variable, variable + 1]).unwrap());
+    }
+    let result = basic_dpll_with_config(chain.clone(), &SolverConfig::default());
+    assert_eq!(ClauseType::Unsatisfiable, result.clause_type);
+    assert_eq!((0, Some(TrivialReason::UnitPropagation)),
+               (result.decisions, result.trivial_reason));
+    chain.remove_clause(&CNFClause::from_literals(&[-5000, -4999]).unwrap());
+    let result = basic_dpll_with_config(chain.clone(), &SolverConfig::default());
+    assert_eq!(ClauseType::Satisfiable, result.clause_type);
+    assert_eq!((0, Some(TrivialReason::UnitPropagation)),
+               (result.decisions, result.trivial_reason));
+    assert!(satisfies(&chain, &result.model));
+
+    // The solvers report why, and only when they didn't need to search
+    let result = basic_dpll_with_config(system_from(&[&[4], &[-4]]), &SolverConfig::default());
+    assert_eq!(ClauseType::Unsatisfiable, result.clause_type);
+    assert_eq!(Some(TrivialReason::ConflictingUnits(4)), result.trivial_reason);
+    let result = cube_and_conquer(system_from(&[&[1, 2], &[-1, 2]]), 4, 2);
+    assert_eq!(ClauseType::Satisfiable, result.clause_type);
+    assert_eq!(None, result.trivial_reason);
+}
+
+#[test]
+fn test_solve_errors() {
+    use testutil::{pigeonhole, system_from};
+
+    let config = SolverConfig{ conflict_budget: Some(10), ..SolverConfig::default() };
+    assert_eq!(Err(SolveError::BudgetExceeded), try_basic_dpll(pigeonhole(5), &config));
+    assert_eq!(Err(SolveError::BudgetExceeded), try_cube_and_conquer(pigeonhole(5), 4, 2, &config));
+    let result = try_basic_dpll(system_from(&[&[1, 2], &[-1, 2]]), &config).unwrap();
+    assert_eq!(ClauseType::Satisfiable, result.clause_type);
+    // A system that's trivially Unsatisfiable is a verdict, not an error
+    let result = try_basic_dpll(system_from(&[&[1, 2], &[]]), &config).unwrap();
+    assert_eq!(ClauseType::Unsatisfiable, result.clause_type);
+
+    let config = SolverConfig::default();
+    config.cancellation.cancel();
+    assert_eq!(Err(SolveError::Interrupted), try_basic_dpll(pigeonhole(3), &config));
+    assert_eq!(Err(SolveError::Interrupted), try_cube_and_conquer(pigeonhole(3), 4, 2, &config));
+    assert_eq!("the solve was cancelled", SolveError::Interrupted.to_string());
+}
+
+#[test]
+fn test_solve_with_conflict_budget() {
+    use testutil::{pigeonhole, system_from};
+
+    let result = solve_with_conflict_budget(pigeonhole(6), 100);
+    assert_eq!(ClauseType::Unknown, result.clause_type);
+    assert_eq!(101, result.conflicts);
+
+    let result = solve_with_conflict_budget(system_from(&[&[1], &[-1, 2], &[-2, 3, 4]]), 0);
+    assert_eq!(ClauseType::Satisfiable, result.clause_type);
+    assert_eq!(0, result.conflicts);
+    let result = solve_with_conflict_budget(pigeonhole(2), 1000);
+    assert_eq!(ClauseType::Unsatisfiable, result.clause_type);
+    assert!(result.conflicts <= 1000);
+
+    // The budget is for each solve, not for every solve using the config
+    let config = SolverConfig{ conflict_budget: Some(1000), ..SolverConfig::default() };
+    for _ in 0..3 {
+        assert_eq!(ClauseType::Unsatisfiable,
+                   basic_dpll_with_config(pigeonhole(3), &config).clause_type);
+    }
+}
+
+#[test]
+fn test_dpll_is_deterministic() {
+    use testutil::{XorShift, random_system, system_from};
+
+    // Every system built from the same clauses iterates them in its own order, so rebuild it for
+    // every solve
+    let mut rng = XorShift::new(150);
+    let clauses = random_system(&mut rng, 12, 30, 3).clauses.into_iter()
+        .map(|clause| clause.iter().cloned().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let build = || system_from(&clauses.iter().map(|clause| &clause[..]).collect::<Vec<_>>());
+    let expected = (basic_dpll(build()), concurrent_dpll(build(), HashSet::new(), 4));
+    assert_eq!(ClauseType::Satisfiable, expected.0 .0);
+    for _ in 0..100 {
+        assert_eq!(expected, (basic_dpll(build()), concurrent_dpll(build(), HashSet::new(), 4)));
+    }
+}
+
+#[test]
+fn test_concurrent_dpll_without_threads() {
+    use testutil::{pigeonhole, satisfies, system_from, XorShift, random_system};
+
+    let mut rng = XorShift::new(155);
+    for _ in 0..20 {
+        let system = random_system(&mut rng, 12, 50, 3);
+        for &thread_count in &[1, 0, -1] {
+            let (clause_type, model) = concurrent_dpll(system.clone(), HashSet::new(),
+                                                       thread_count);
+            assert_eq!(basic_dpll(system.clone()).0, clause_type);
+            if clause_type == ClauseType::Satisfiable {
+                assert!(satisfies(&system, &model));
+            }
+        }
+    }
+    assert_eq!(ClauseType::Unsatisfiable, concurrent_dpll(pigeonhole(3), HashSet::new(), 1).0);
+
+    // The units given are still set, and are part of the model
+    let system = system_from(&[&[1, 2], &[-1, 3]]);
+    let (clause_type, model) = concurrent_dpll(system.clone(), vec![1].into_iter().collect(), 1);
+    assert_eq!(ClauseType::Satisfiable, clause_type);
+    assert!(model.contains(&1) && model.contains(&3));
+    let units = vec![1, -3].into_iter().collect();
+    assert_eq!(ClauseType::Unsatisfiable, concurrent_dpll(system, units, 1).0);
+
+    // Units given by the caller get the same verdict whether threads are used or not, including
+    // units that don't occur in the system, and every one of them is in the model
+    let system = system_from(&[&[1, 2]]);
+    for units in &[vec![1, 5, -5], vec![1, 7], vec![-2, -1], vec![2]] {
+        let units = units.iter().cloned().collect::<HashSet<_>>();
+        let sequential = concurrent_dpll(system.clone(), units.clone(), 1);
+        let threaded = concurrent_dpll(system.clone(), units.clone(), 4);
+        assert_eq!(sequential.0, threaded.0);
+        if sequential.0 == ClauseType::Satisfiable {
+            for model in &[sequential.1, threaded.1] {
+                assert!(satisfies(&system, model) && units.iter().all(|unit| model.contains(unit)));
+            }
+        }
+    }
+    let units = vec![1, 5, -5].into_iter().collect();
+    assert_eq!(ClauseType::Unsatisfiable, concurrent_dpll(system.clone(), units, 4).0);
+    let mut rng = XorShift::new(1155);
+    for _ in 0..20 {
+        let system = random_system(&mut rng, 10, 36, 3);
+        let units = (0..2).map(|_| {
+            let variable = (rng.below(12) + 1) as isize;
+            if rng.below(2) == 0 { variable } else { -variable }
+        }).collect::<HashSet<_>>();
+        assert_eq!(concurrent_dpll(system.clone(), units.clone(), 1).0,
+                   concurrent_dpll(system, units, 4).0);
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_rayon_dpll() {
+    use testutil::{pigeonhole, satisfies, XorShift, random_system};
+
+    let mut rng = XorShift::new(165);
+    for _ in 0..30 {
+        let system = random_system(&mut rng, 20, 86, 3);
+        let expected = basic_dpll(system.clone()).0;
+        for &thread_count in &[1, 2, 4] {
+            let result = rayon_dpll(system.clone(), thread_count, &SolverConfig::default());
+            assert_eq!(expected, result.clause_type);
+            if result.clause_type == ClauseType::Satisfiable {
+                assert!(satisfies(&system, &result.model));
+            }
+        }
+    }
+    let result = rayon_dpll(pigeonhole(4), 4, &SolverConfig::default());
+    assert_eq!(ClauseType::Unsatisfiable, result.clause_type);
+
+    // Cancelling the solve as a whole stops every branch
+    let config = SolverConfig::default();
+    config.cancellation.cancel();
+    assert_eq!(ClauseType::Unknown, rayon_dpll(pigeonhole(4), 4, &config).clause_type);
+}
+
+#[test]
+fn test_dpll_mom_heuristic() {
+    use testutil::{pigeonhole, satisfies, XorShift, random_system};
+
+    let config = SolverConfig{ heuristic: DecisionHeuristic::Mom, ..SolverConfig::default() };
+    let mut rng = XorShift::new(166);
+    for _ in 0..20 {
+        let system = random_system(&mut rng, 15, 64, 3);
+        let result = basic_dpll_with_config(system.clone(), &config);
+        assert_eq!(basic_dpll(system.clone()).0, result.clause_type);
+        if result.clause_type == ClauseType::Satisfiable {
+            assert!(satisfies(&system, &result.model));
+        }
+        let result = concurrent_dpll_with_config(system.clone(), HashSet::new(), 4, &config);
+        assert_eq!(basic_dpll(system).0, result.clause_type);
+    }
+    let result = basic_dpll_with_config(pigeonhole(3), &config);
+    assert_eq!(ClauseType::Unsatisfiable, result.clause_type);
+}
+
+#[test]
+fn test_dpll_longest_clause_heuristic() {
+    use testutil::{pigeonhole, satisfies, XorShift, random_system};
+
+    let config = SolverConfig{ heuristic: DecisionHeuristic::LongestClause,
+                               ..SolverConfig::default() };
+    let mut rng = XorShift::new(184);
+    let mut decisions_differ = false;
+    for _ in 0..20 {
+        let system = random_system(&mut rng, 15, 64, 3);
+        let result = basic_dpll_with_config(system.clone(), &config);
+        let lowest_variable_result = basic_dpll_with_config(system.clone(),
+                                                            &SolverConfig::default());
+        assert_eq!(lowest_variable_result.clause_type, result.clause_type);
+        if result.clause_type == ClauseType::Satisfiable {
+            assert!(satisfies(&system, &result.model));
+        }
+        decisions_differ |= result.decisions != lowest_variable_result.decisions;
+    }
+    assert!(decisions_differ);
+    let result = basic_dpll_with_config(pigeonhole(3), &config);
+    assert_eq!(ClauseType::Unsatisfiable, result.clause_type);
+}
+
+#[test]
+fn test_dpll_trace() {
+    use testutil::system_from;
+
+    let lines = Arc::new(Mutex::new(vec![]));
+    let config = SolverConfig{ trace: Some(TraceOutput::Lines(lines.clone())),
+                               ..SolverConfig::default() };
+    let system = system_from(&[&[1, 2], &[1, -2], &[-1, 2], &[-1, -2]]);
+    basic_dpll_with_config(system, &config);
+    assert_eq!(vec!["-1", "-1 -> Unsatisfiable", "1", "1 -> Unsatisfiable"],
+               *lines.lock().unwrap());
+
+    // A branch's decisions are indented under it
+    lines.lock().unwrap().clear();
+    let system = system_from(&[&[1, 2, 3], &[1, -2, -3], &[-1, 2, -3], &[-1, -2, 3]]);
+    basic_dpll_with_config(system, &config);
+    assert_eq!(vec!["-1", "  -2", "  -2 -> Satisfiable", "-1 -> Satisfiable"],
+               *lines.lock().unwrap());
+
+    lines.lock().unwrap().clear();
+    basic_dpll_with_config(system_from(&[&[1, 2, 3], &[-1, -2, 3]]), &SolverConfig::default());
+    assert!(lines.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_configs_agree() {
+    use testutil::{satisfies, XorShift, random_system};
+
+    let heuristic = |heuristic| SolverConfig{ heuristic, ..SolverConfig::default() };
+    let configs = [heuristic(DecisionHeuristic::Mom), heuristic(DecisionHeuristic::LongestClause),
+                   SolverConfig{ phases: (1..13).map(|variable| (variable, true)).collect(),
+                                 ..SolverConfig::default() }];
+    let mut rng = XorShift::new(191);
+    for _ in 0..30 {
+        let system = random_system(&mut rng, 12, 52, 3);
+        for each_config in &configs {
+            let comparison = compare_solvers(&system, SolverConfig::default(), each_config.clone());
+            assert!(comparison.verdicts_agree(), "{:?}", comparison);
+            for result in &[comparison.a, comparison.b] {
+                if result.clause_type == ClauseType::Satisfiable {
+                    assert!(satisfies(&system, &result.model));
+                }
+            }
         }
     }
 }
+
+#[test]
+fn test_dpll_initial_assignment() {
+    use testutil::{satisfies, XorShift, random_system};
+
+    let mut rng = XorShift::new(173);
+    let mut solved = 0;
+    while solved < 10 {
+        let system = random_system(&mut rng, 15, 50, 3);
+        let (clause_type, model) = basic_dpll(system.clone());
+        if clause_type != ClauseType::Satisfiable {
+            continue;
+        }
+        // Starting from a model, every decision agrees with it so there's nothing to backtrack
+        let mut config = SolverConfig::default();
+        config.set_initial_assignment(&model);
+        let result = basic_dpll_with_config(system.clone(), &config);
+        assert_eq!(ClauseType::Satisfiable, result.clause_type);
+        assert_eq!(0, result.conflicts);
+        assert!(result.model.is_subset(&model));
+        assert!(satisfies(&system, &result.model));
+        solved += 1;
+    }
+}
+
+#[test]
+fn test_conflict_histogram() {
+    use testutil::system_from;
+
+    // Only variables 7, 8 and 9 can't be satisfied, but the solver branches on 1 to 4 first
+    let system = system_from(&[&[1, 2, -3], &[-1, 3, 4], &[-2, -4, 3], &[1, -4, 2],
+                               &[7, 8], &[7, -8], &[-7, 9], &[-7, -9]]);
+    let config = SolverConfig::default();
+    let result = basic_dpll_with_config(system, &config);
+    assert_eq!(ClauseType::Unsatisfiable, result.clause_type);
+    let histogram = config.stats.conflict_histogram();
+    assert_eq!(result.conflicts as u64, histogram.values().sum::<u64>());
+    let (&most_conflicted, _) = histogram.iter().max_by_key(|&(_, &count)| count).unwrap();
+    assert!([7, 8, 9].contains(&most_conflicted));
+    assert!(SolverConfig::default().stats.conflict_histogram().is_empty());
+}
+
+#[test]
+fn test_component_dpll() {
+    use testutil::{satisfies, system_from};
+
+    let system = system_from(&[&[1, 2], &[-2, 3], &[-1, -3], &[4, -5], &[5, 6, 7], &[-7]]);
+    let (clause_type, interpretation) = component_dpll(system.clone());
+    assert_eq!(ClauseType::Satisfiable, clause_type);
+    assert!(satisfies(&system, &interpretation));
+
+    // Making just one of the halves Unsatisfiable makes the whole system Unsatisfiable
+    let system = system_from(&[&[1, 2], &[-2, 3], &[-1, -3], &[4, -5], &[5], &[-4]]);
+    assert_eq!(ClauseType::Unsatisfiable, component_dpll(system).0);
+}
+
+#[test]
+fn test_equivalent() {
+    use testutil::system_from;
+
+    // Leaving out the clauses that are subsumed by others doesn't change the meaning
+    let system = system_from(&[&[1, 2], &[1], &[-1, 3], &[-1, 3, 4], &[2, 3, -4]]);
+    let simplified = system_from(&[&[1], &[-1, 3]]);
+    assert!(equivalent(&system, &simplified));
+    assert!(equivalent(&simplified, &system));
+
+    // ...but leaving out other clauses does
+    assert!(!equivalent(&system, &system_from(&[&[1]])));
+    assert!(!equivalent(&system_from(&[&[1, 2]]), &system_from(&[&[1, -2]])));
+    assert!(!equivalent(&system_from(&[&[1]]), &system_from(&[&[1], &[2]])));
+
+    assert!(equivalent(&CNFSystem::new(None), &CNFSystem::new(None)));
+    assert!(!equivalent(&CNFSystem::new(None), &system_from(&[&[1]])));
+}
+
+#[test]
+fn test_basic_dpll_recording_tree() {
+    use testutil::system_from;
+
+    // Every assignment of 1 and 2 falsifies a clause, but only after deciding one of them, so
+    // both polarities of the first variable are tried before giving up
+    let system = system_from(&[&[1, 2], &[-1, 2], &[1, -2], &[-1, -2]]);
+    let mut tree = SearchTree::new();
+    let result = basic_dpll_recording_tree(system, &SolverConfig::default(), &mut tree);
+    assert_eq!(ClauseType::Unsatisfiable, result.clause_type);
+    assert_eq!(2, tree.decision_count());
+    assert_eq!(2, result.decisions);
+
+    let mut output = vec![];
+    tree.write_dot(&mut output).unwrap();
+    let dot = String::from_utf8(output).unwrap();
+    assert_eq!(2, dot.matches("n0 -> n").count());
+    assert_eq!(2, dot.matches("[label=\"UNSAT\", shape=box]").count());
+    assert_eq!(0, dot.matches("[label=\"SAT\", shape=box]").count());
+}
+
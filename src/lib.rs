@@ -0,0 +1,46 @@
+#[cfg(feature = "gzip")]
+extern crate flate2;
+#[cfg(feature = "xz")]
+extern crate lzma_rs;
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+
+pub mod cnf_system;
+pub mod dpll;
+pub mod parser;
+pub mod brute_force;
+pub mod var_map;
+pub mod search_tree;
+pub mod rng;
+pub mod walksat;
+pub mod models;
+pub mod clause_pool;
+pub mod assignment;
+pub mod tagged;
+pub mod proof;
+#[cfg(test)]
+mod testutil;
+
+use cnf_system::{CNFClause, CNFSystem, ClauseType};
+
+/// Returns true if the clauses, each given as a list of non-zero literals, can all be true at once.
+/// An empty clause can never be true, and an empty list of clauses always is.
+///
+/// ```
+/// assert!(!rusat::is_satisfiable(&[&[1, 2], &[-1], &[-2]]));
+/// assert!(rusat::is_satisfiable(&[&[1, 2], &[-1]]));
+/// assert!(!rusat::is_satisfiable(&[&[]]));
+/// assert!(rusat::is_satisfiable(&[]));
+/// ```
+///
+/// Panics if any literal is 0.
+pub fn is_satisfiable(clauses: &[&[isize]]) -> bool {
+    let mut system = CNFSystem::new(None);
+    for each_clause in clauses {
+        system.add_clause(CNFClause::from_literals(each_clause).expect("a literal can't be 0"));
+    }
+    dpll::basic_dpll(system).0 == ClauseType::Satisfiable
+}
@@ -1,10 +1,28 @@
 use std::{io, process, env};
-use std::io::prelude::*;
-use std::collections::HashSet;
-mod cnf_system;
-use cnf_system::{CNFSystem, CNFClause, ClauseType};
-mod dpll;
-use dpll::{concurrent_dpll};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use std::thread;
+extern crate rusat;
+#[cfg(all(test, feature = "xz"))]
+extern crate lzma_rs;
+#[cfg(feature = "log")]
+extern crate log;
+use rusat::cnf_system::{CNFClause, CNFSystem, ClauseType, SimplifyReport};
+use rusat::dpll::{assumption_core, basic_dpll_recording_tree, basic_dpll_with_config,
+                  cube_and_conquer_with_config, SolveResult, SolverConfig, TraceOutput};
+use rusat::parser::{decompress, find_dimacs_files, format_model, parse_assignment, parse_dimacs,
+                    parse_icnf, parse_solution, IncrementalProblem, ModelFormat, ParseError,
+                    ParseOptions, ParsedSystem};
+use rusat::search_tree::SearchTree;
+use rusat::rng::XorShift;
+use rusat::var_map::VarMap;
+
+// How many cubes to split the search into for each thread, so that threads which finish their cube
+// early have more to take
+const CUBES_PER_THREAD: usize = 4;
 
 // Show help and exit
 fn show_help(program_name: String) {
@@ -12,7 +30,52 @@ fn show_help(program_name: String) {
     println!("Mandatory arguments to long options are mandatory for short options too.");
     println!("
 -f, --file FILE         Read in the system from FILE. If FILE is ``-'', then
-                        input is read from stdin. Default: ``-''.
+                        input is read from stdin. Default: ``-''. Input may be gzip
+                        or xz compressed.
+    --format FORMAT     Read the input as ``cnf'', i.e. DIMACS, or as ``icnf'', i.e.
+                        clauses mixed with ``a'' lines, solving the clauses so far
+                        under the literals of each ``a'' line and outputting a result
+                        for each one. Default: cnf.
+    --assign FILE       Fix the literals listed in FILE as true before solving, along
+                        with any on ``a'' lines of the input. If that makes the system
+                        unsatisfiable, also output a minimal set of them that does as a
+                        ``c assumption_core=...'' line.
+    --keep-tautologies  Keep tautological clauses in the system as read, e.g. so that
+                        --analyze counts them. They're still removed before solving.
+    --strict            Warn about input that is accepted but isn't strictly valid
+                        DIMACS, and reject a SATLIB ``%'' line, a ``+'' sign or an
+                        ``a'' line.
+    --dir DIRECTORY     Solve every .cnf, .cnf.gz and .cnf.xz file in DIRECTORY and its
+                        subdirectories, outputting a line of each file's result and time
+                        and then the amount of each result.
+    --verify FILE       Check the model on the ``v'' lines of FILE, e.g. another solver's
+                        output, against the system instead of solving it. Outputs
+                        VERIFIED, or FAILED and the number in the input of the first
+                        clause it leaves unsatisfied, counting from 1.
+    --shuffle SEED      Renumber the variables in a random order, seeded by SEED, before
+                        solving. The result is the same, but the solver's path isn't,
+                        which helps to find bugs that depend on the order. Can't be
+                        used with --tree or --trace.
+    --tree FILE         Solve sequentially and write the search tree to FILE as a
+                        Graphviz DOT graph. Only sensible for small systems.
+    --trace             Solve sequentially and print each branch of the search to stderr:
+                        its decision when the branch is entered and its verdict when it's
+                        left, indented by decision level. Only sensible for small systems.
+    --threads COUNT     Solve with COUNT threads. Default: the amount of CPUs.
+    --model-format FORMAT
+                        Output a model as ``signed'' literals, e.g. {{-1, 2}}, or as
+                        each variable's ``assign''ment, e.g. 1=false 2=true.
+                        Default: signed.
+    --analyze           Output metrics of the system as ``c key=value'' lines and exit
+                        without solving it.
+    --simplify          Remove tautologies, propagate units and remove subsumed clauses,
+                        then output how much smaller the system got as ``c key=value''
+                        lines and exit without solving it.
+    --solve             With --analyze or --simplify, solve the (simplified) system
+                        afterwards instead of exiting.
+-V, --verbosity LEVEL   0 to output only the result, 1 to also output statistics of the
+                        solve as ``c key=value'' lines, 2 to also output progress while
+                        solving. Default: 0.
 -v, --version           Output version and exit, regardless of other arguments.
 -h, -?, --help          Output usage and exit, regardless of other arguments."
             );
@@ -24,10 +87,197 @@ fn show_version() {
     println!("License GPLv3+: GNU GPL version 3 or later <https://gnu.org/licenses/gpl.html>");
     println!("This is free software, and you are welcome to redistribute it");
     println!("under certain conditions.");
-    println!("");
+    println!();
     println!("Home: https://github.com/rolag/ruSAT/");
 }
 
+// Output metrics of the system, one ``c key=value'' line each
+fn show_analysis(system: &CNFSystem) {
+    let histogram = system.clause_length_histogram();
+    println!("c variables={}", system.variables().len());
+    println!("c clauses={}", system.len());
+    for (length, count) in &histogram {
+        println!("c clause_length_{}={}", length, count);
+    }
+    println!("c unit_clauses={}", histogram.get(&1).cloned().unwrap_or(0));
+    println!("c horn={}", system.is_horn());
+    println!("c 2sat={}", system.is_2sat());
+    println!("c pure_literals={}", system.pure_literals().len());
+}
+
+// Solve each file on its own, outputting a line of its result and time as it's done, and then
+// how many files had each result
+fn solve_files<W: Write>(files: &[PathBuf], parse_options: &ParseOptions, thread_count: usize,
+                         config: &SolverConfig, output: &mut W) -> io::Result<()> {
+    let mut tally: BTreeMap<&str, usize> = BTreeMap::new();
+    for each_file in files {
+        let started_at = Instant::now();
+        let verdict = match solve_file(each_file, parse_options, thread_count, config) {
+            Ok(ClauseType::Satisfiable)   => "SATISFIABLE",
+            Ok(ClauseType::Unsatisfiable) => "UNSATISFIABLE",
+            Ok(ClauseType::Tautology)     => "TAUTOLOGY",
+            Ok(ClauseType::Unknown)       => "UNKNOWN",
+            Err(error) => {
+                eprintln!("{}: {}", each_file.display(), error);
+                "ERROR"
+            },
+        };
+        writeln!(output, "{} {} {}ms", each_file.display(), verdict,
+                 started_at.elapsed().as_millis())?;
+        *tally.entry(verdict).or_insert(0) += 1;
+    }
+    for verdict in &["SATISFIABLE", "UNSATISFIABLE", "TAUTOLOGY", "UNKNOWN", "ERROR"] {
+        writeln!(output, "c {}={}", verdict.to_lowercase(), tally.get(verdict).unwrap_or(&0))?;
+    }
+    Ok(())
+}
+
+// Solve each query of an ICNF input in turn, with the clauses read before it and its assumptions,
+// outputting a result for each one
+fn solve_incremental(problem: &IncrementalProblem, thread_count: usize, config: &SolverConfig,
+                     model_format: ModelFormat) {
+    let mut system = CNFSystem::new(None);
+    let mut clauses_added = 0;
+    for each_query in &problem.queries {
+        for each_clause in &problem.clauses[clauses_added..each_query.clause_count] {
+            if !each_clause.is_tautology() {
+                system.add_clause(each_clause.clone());
+            }
+        }
+        clauses_added = each_query.clause_count;
+
+        let mut assumed_system = system.clone();
+        for &literal in &each_query.assumptions {
+            let mut unit = CNFClause::new();
+            unit.add(literal);
+            assumed_system.add_clause(unit);
+        }
+        let mut var_map = VarMap::new();
+        let mut result = solve(var_map.compact(&assumed_system), thread_count, config);
+        result.model = var_map.expand_model(&result.model);
+        result.trivial_reason = result.trivial_reason.map(|reason| reason.expand(&var_map));
+        if config.verbosity >= 1 {
+            show_stats(&result);
+        }
+        match result.into() {
+            (ClauseType::Satisfiable, interpretation) => {
+                println!("SATISFIABLE: {}", format_model(&interpretation, model_format))
+            },
+            (ClauseType::Unsatisfiable, _) => println!("UNSATISFIABLE"),
+            (_, _)                         => println!("UNKNOWN"),
+        }
+    }
+}
+
+// Read in and solve one file for --dir, along with the assumptions of its 'a' lines
+fn solve_file(file: &Path, parse_options: &ParseOptions, thread_count: usize,
+              config: &SolverConfig) -> Result<ClauseType, ParseError> {
+    let input = decompress(BufReader::new(File::open(file)?))?;
+    let ParsedSystem { mut system, contains_tautologies, mut var_map, assumptions, .. } =
+        parse_dimacs(input, parse_options)?;
+    system.remove_tautologies();
+    if system.is_empty() && contains_tautologies && assumptions.is_empty() {
+        return Ok(ClauseType::Tautology);
+    }
+    for literal in assumptions {
+        let mut unit = CNFClause::new();
+        unit.add(literal);
+        system.add_clause(unit);
+    }
+    let system = var_map.compact(&system);
+    Ok(solve(system, thread_count, config).clause_type)
+}
+
+// Output how much simplifying the system removed, one ``c key=value'' line each
+fn show_simplify_report(report: &SimplifyReport) {
+    println!("c clauses_before={}", report.clauses_before);
+    println!("c clauses_after={}", report.clauses_after);
+    println!("c clauses_removed={}", report.clauses_before - report.clauses_after);
+    println!("c variables_before={}", report.variables_before);
+    println!("c variables_after={}", report.variables_after);
+    println!("c variables_eliminated={}", report.variables_before - report.variables_after);
+    println!("c literals_before={}", report.literals_before);
+    println!("c literals_after={}", report.literals_after);
+    println!("c literals_removed={}", report.literals_before - report.literals_after);
+    println!("c tautologies_removed={}", report.tautologies_removed);
+    println!("c subsumed_removed={}", report.subsumed_removed);
+    println!("c forced_literals={}", report.forced_literals.len());
+    println!("c unsatisfiable={}", report.unsatisfiable);
+}
+
+// Add each literal being assumed to the system as a unit. Simplifying takes the literals it
+// forces out of the system, so any of them that an assumption contradicts is put back as a unit
+// too, or the solver would never see the conflict
+fn add_assumptions(system: &mut CNFSystem, assumptions: &[isize],
+                   forced_literals: &BTreeSet<isize>) {
+    for &literal in assumptions {
+        let mut unit = CNFClause::new();
+        unit.add(literal);
+        system.add_clause(unit);
+        if forced_literals.contains(&-literal) {
+            let mut forced_unit = CNFClause::new();
+            forced_unit.add(-literal);
+            system.add_clause(forced_unit);
+        }
+    }
+}
+
+// Output the work done by the solve, one ``c key=value'' line each
+fn show_stats(result: &SolveResult) {
+    println!("c decisions={}", result.decisions);
+    println!("c propagations={}", result.propagations);
+    println!("c conflicts={}", result.conflicts);
+    println!("c max_decision_level={}", result.max_decision_level);
+    println!("c time_ms={}", result.time.as_millis());
+    if let Some(reason) = result.trivial_reason {
+        println!("c trivial_reason={}", reason);
+    }
+}
+
+// Output the solvers' log messages as ``c'' lines, the way they're printed without the log feature
+#[cfg(feature = "log")]
+struct CommentLogger;
+
+#[cfg(feature = "log")]
+impl log::Log for CommentLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            println!("c {}", record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+// Log the solvers' progress only at verbosity 2, as it's printed without the log feature
+#[cfg(feature = "log")]
+fn init_logging(verbosity: u8) {
+    static LOGGER: CommentLogger = CommentLogger;
+    if log::set_logger(&LOGGER).is_ok() {
+        let level = if verbosity >= 2 { log::LevelFilter::Info } else { log::LevelFilter::Warn };
+        log::set_max_level(level);
+    }
+}
+
+// Solve the system with `thread_count` threads, on rayon's thread pool if it's built in
+#[cfg(feature = "rayon")]
+fn solve(system: CNFSystem, thread_count: usize, config: &SolverConfig) -> SolveResult {
+    if thread_count > 1 {
+        rusat::dpll::rayon_dpll(system, thread_count, config)
+    } else {
+        cube_and_conquer_with_config(system, CUBES_PER_THREAD, 1, config)
+    }
+}
+
+#[cfg(not(feature = "rayon"))]
+fn solve(system: CNFSystem, thread_count: usize, config: &SolverConfig) -> SolveResult {
+    cube_and_conquer_with_config(system, CUBES_PER_THREAD * thread_count, thread_count, config)
+}
+
 fn get_next_arg_or_err<'a>(program_name: &str, args: &'a [String], current_index: usize) -> &'a str {
     let arg_count = args.len();
     if current_index == arg_count - 1 {
@@ -55,6 +305,19 @@ fn main() {
 
     // Set argument defaults
     let mut input_file = "-";
+    let mut parse_options = ParseOptions::default();
+    let mut analyze = false;
+    let mut simplify = false;
+    let mut solve_anyway = false;
+    let mut tree_file = None;
+    let mut assign_file = None;
+    let mut verify_file = None;
+    let mut shuffle_seed = None;
+    let mut batch_directory = None;
+    let mut incremental = false;
+    let mut config = SolverConfig::default();
+    let mut thread_count = None;
+    let mut model_format = ModelFormat::Signed;
 
     // Loop through each argument, changing argument options when necessary
     let mut arg_index = 1;
@@ -64,6 +327,90 @@ fn main() {
                 input_file = get_next_arg_or_err(&program_name, &args, arg_index);
                 arg_index += 1;
             },
+            "--strict" => {
+                parse_options.strict = true;
+            },
+            "--keep-tautologies" => {
+                parse_options.keep_tautologies = true;
+            },
+            "--assign" => {
+                assign_file = Some(get_next_arg_or_err(&program_name, &args, arg_index));
+                arg_index += 1;
+            },
+            "--format" => {
+                let format = get_next_arg_or_err(&program_name, &args, arg_index);
+                incremental = match format {
+                    "cnf"  => false,
+                    "icnf" => true,
+                    _ => error_and_exit(&program_name,
+                                        format!("not an input format: {}", format),
+                                        22),
+                };
+                arg_index += 1;
+            },
+            "--dir" => {
+                batch_directory = Some(get_next_arg_or_err(&program_name, &args, arg_index));
+                arg_index += 1;
+            },
+            "--verify" => {
+                verify_file = Some(get_next_arg_or_err(&program_name, &args, arg_index));
+                arg_index += 1;
+            },
+            "--shuffle" => {
+                let seed = get_next_arg_or_err(&program_name, &args, arg_index);
+                shuffle_seed = match seed.parse::<u64>() {
+                    Ok(seed) => Some(seed),
+                    _ => error_and_exit(&program_name, format!("not a seed: {}", seed), 22),
+                };
+                arg_index += 1;
+            },
+            "--tree" => {
+                tree_file = Some(get_next_arg_or_err(&program_name, &args, arg_index));
+                arg_index += 1;
+            },
+            "--trace" => {
+                config.trace = Some(TraceOutput::Stderr);
+            },
+            "--threads" => {
+                let count = get_next_arg_or_err(&program_name, &args, arg_index);
+                thread_count = match count.parse::<usize>() {
+                    Ok(count) if count >= 1 => Some(count),
+                    _ => error_and_exit(&program_name,
+                                        format!("not a thread count: {}", count),
+                                        22),
+                };
+                arg_index += 1;
+            },
+            "--model-format" => {
+                let format = get_next_arg_or_err(&program_name, &args, arg_index);
+                model_format = match format {
+                    "signed" => ModelFormat::Signed,
+                    "assign" => ModelFormat::Assign,
+                    _ => error_and_exit(&program_name,
+                                        format!("not a model format: {}", format),
+                                        22),
+                };
+                arg_index += 1;
+            },
+            "--analyze" => {
+                analyze = true;
+            },
+            "--simplify" => {
+                simplify = true;
+            },
+            "--solve" => {
+                solve_anyway = true;
+            },
+            "-V" | "--verbosity" => {
+                let level = get_next_arg_or_err(&program_name, &args, arg_index);
+                config.verbosity = match level.parse::<u8>() {
+                    Ok(level) if level <= 2 => level,
+                    _ => error_and_exit(&program_name,
+                                        format!("not a verbosity level: {}", level),
+                                        22),
+                };
+                arg_index += 1;
+            },
             "-v" | "--version" => {
                 show_version();
                 process::exit(0);
@@ -81,82 +428,284 @@ fn main() {
         }
         arg_index += 1;
     }
+    // Trees and traces use the input's variable numbers, so there's nothing to shuffle
+    if shuffle_seed.is_some() && (tree_file.is_some() || config.trace.is_some()) {
+        error_and_exit(&program_name, "--shuffle can't be used with --tree or --trace".to_string(),
+                       22);
+    }
+    #[cfg(feature = "log")]
+    init_logging(config.verbosity);
 
-    let input;
-    if input_file == "-" {
-        // Read in CNF system from stdin in dimacs form, for now
-        input = io::stdin();
-    } else {
-        // TODO: Read from file
-        println!("reading from file not implemented yet");
-        process::exit(38);
+    let thread_count = thread_count.unwrap_or_else(|| {
+        thread::available_parallelism().map_or(1, |count| count.get())
+    });
+
+    if let Some(directory) = batch_directory {
+        let files = match find_dimacs_files(Path::new(directory)) {
+            Ok(files)  => files,
+            Err(error) => error_and_exit(&program_name, format!("{}: {}", directory, error), 2),
+        };
+        if let Err(error) = solve_files(&files, &parse_options, thread_count, &config,
+                                        &mut io::stdout()) {
+            error_and_exit(&program_name, error.to_string(), 5);
+        }
+        process::exit(0);
     }
 
-    let mut system = CNFSystem::new(None);
-    let mut contains_tautologies = false;
-    // The units that exist in the input system, before any algorithm is applied to it
-    let mut units = HashSet::new();
-
-    // Skip all comment lines i.e. a line that begins with 'c' and the program line i.e. a line
-    // like 'p VARIABLE_COUNT CLAUSE_COUNT'
-    'next_line: for lines in input.lock().lines() {
-        let current_line: String = lines.unwrap(); // expect valid string from stdin
-        let words = current_line.split_whitespace().collect::<Vec<_>>();
-        if let Some(first_word) = words.iter().next() {
-            if let Some(first_char) = first_word.chars().next() {
-                if first_char == 'c' || first_char == 'p' {
-                    continue;
-                }
-            } else {
-                continue;
-            }
+    if incremental {
+        let parse_result = if input_file == "-" {
+            let input = io::stdin();
+            decompress(input.lock()).map_err(ParseError::from)
+                                    .and_then(|input| parse_icnf(input, &parse_options))
         } else {
-            continue;
-        }
-        // Now, insert the actual input into the system
-        let mut current_clause = CNFClause::new();
-        for each_word in words {
-            // Convert word to integer
-            let literal = match each_word.parse::<isize>() {
-                Ok(word) => word,
-                Err(_) => error_and_exit(&program_name,
-                                         format!("not a valid comment, program or input line: {}",
-                                                 current_line),
-                                         22),
-            };
-            // Check for tautologies
-            if literal == 0 {
-                break;
-            }
-            if current_clause.contains(-literal) {
-                contains_tautologies = true;
-                continue 'next_line;
-            } else {
-                current_clause.add(literal);
+            match File::open(input_file) {
+                Ok(file)   => {
+                    decompress(BufReader::new(file))
+                        .map_err(ParseError::from)
+                        .and_then(|input| parse_icnf(input, &parse_options))
+                },
+                Err(error) => {
+                    error_and_exit(&program_name, format!("{}: {}", input_file, error), 2)
+                },
             }
+        };
+        match parse_result {
+            Ok(problem) => solve_incremental(&problem, thread_count, &config, model_format),
+            Err(error)  => error_and_exit(&program_name, error.to_string(), 22),
         }
-        if current_clause.len() > 0 {
-            if current_clause.len() == 1 {
-                units.insert(current_clause.iter().next().unwrap().clone());
-            }
-            system.add_clause(current_clause);
+        process::exit(0);
+    }
+
+    // A failed check names the clause by its place in the input
+    parse_options.keep_clause_numbers = verify_file.is_some();
+    let parse_result = if input_file == "-" {
+        // Read in CNF system from stdin in dimacs form
+        let input = io::stdin();
+        decompress(input.lock()).map_err(ParseError::from)
+                                .and_then(|input| parse_dimacs(input, &parse_options))
+    } else {
+        match File::open(input_file) {
+            Ok(file)   => {
+                decompress(BufReader::new(file))
+                    .map_err(ParseError::from)
+                    .and_then(|input| parse_dimacs(input, &parse_options))
+            },
+            Err(error) => error_and_exit(&program_name, format!("{}: {}", input_file, error), 2),
+        }
+    };
+    let parsed = match parse_result {
+        Ok(parsed) => parsed,
+        Err(error) => error_and_exit(&program_name, error.to_string(), 22),
+    };
+    for each_warning in &parsed.warnings {
+        eprintln!("{}: warning: {}", program_name, each_warning);
+    }
+
+    if let Some(verify_file) = verify_file {
+        let parse_result = match File::open(verify_file) {
+            Ok(file)   => parse_solution(BufReader::new(file), &parse_options),
+            Err(error) => error_and_exit(&program_name, format!("{}: {}", verify_file, error), 2),
+        };
+        let model = match parse_result {
+            Ok(model)  => model,
+            Err(error) => error_and_exit(&program_name, format!("{}: {}", verify_file, error), 22),
+        };
+        match parsed.first_unsatisfied_clause(&model) {
+            None => println!("VERIFIED"),
+            Some(number) => {
+                println!("FAILED: clause {} unsatisfied", number);
+                process::exit(1);
+            },
+        }
+        process::exit(0);
+    }
+
+    let ParsedSystem { mut system, contains_tautologies, mut var_map, mut assumptions, .. } =
+        parsed;
+
+    if analyze {
+        show_analysis(&system);
+    }
+    // The literals simplifying forced, which have to be added back to any model
+    let mut forced_literals = BTreeSet::new();
+    // The system as it was before simplifying, to find a core of the assumptions with
+    let mut unsimplified_system = None;
+    if simplify {
+        if solve_anyway {
+            unsimplified_system = Some(system.clone());
         }
+        let report = system.simplify();
+        show_simplify_report(&report);
+        forced_literals = report.forced_literals;
+    }
+    if (analyze || simplify) && !solve_anyway {
+        process::exit(0);
     }
 
-    if system.len() == 0 {
+    // Assume the literals of the input's 'a' lines, then those of the --assign file
+    let assignment = match assign_file {
+        Some(assign_file) => {
+            let parse_result = match File::open(assign_file) {
+                Ok(file)   => parse_assignment(BufReader::new(file), &parse_options),
+                Err(error) => {
+                    error_and_exit(&program_name, format!("{}: {}", assign_file, error), 2)
+                },
+            };
+            match parse_result {
+                Ok(assignment) => {
+                    assumptions.extend(assignment);
+                    assumptions
+                },
+                Err(error) => {
+                    error_and_exit(&program_name, format!("{}: {}", assign_file, error), 22)
+                },
+            }
+        },
+        None => assumptions,
+    };
+
+    system.remove_tautologies();
+    // With literals to assume there's still something to solve, as in solve_file()
+    if system.is_empty() && forced_literals.is_empty() && assignment.is_empty() {
         if contains_tautologies {
             println!("TAUTOLOGY");
         } else {
-            error_and_exit(&program_name, format!("you need to enter a system"), 22);
+            error_and_exit(&program_name, "you need to enter a system".to_string(), 22);
         }
     } else {
         //println!("System: {:?}", system);
 
+        // Keep the system as it was before fixing the assignment, to find a core with if needed
+        let unassigned_system = if assignment.is_empty() {
+            None
+        } else {
+            Some(unsimplified_system.unwrap_or_else(|| system.clone()))
+        };
+        add_assumptions(&mut system, &assignment, &forced_literals);
+
         // Find if the system is satisfiable or unsatisfiable or tautology
-        match concurrent_dpll(system, units, 16) {
+        let mut result = match tree_file {
+            Some(tree_file) => {
+                // Only the sequential solver can record its search, and it does so using the
+                // input's variable numbers
+                let mut tree = SearchTree::new();
+                let result = basic_dpll_recording_tree(system, &config, &mut tree);
+                let written = File::create(tree_file).and_then(|mut file| {
+                    tree.write_dot(&mut file)
+                });
+                if let Err(error) = written {
+                    error_and_exit(&program_name, format!("{}: {}", tree_file, error), 5);
+                }
+                result
+            },
+            None if config.trace.is_some() => {
+                // The search is only traced sequentially, like a tree, so that its lines are in
+                // order and use the input's variable numbers
+                basic_dpll_with_config(system, &config)
+            },
+            None => {
+                // Solve the system using dense variable numbers, translating the model back
+                // afterwards
+                if let Some(seed) = shuffle_seed {
+                    var_map.shuffle(&mut XorShift::new(seed));
+                }
+                let system = var_map.compact(&system);
+                let mut result = solve(system, thread_count, &config);
+                result.model = var_map.expand_model(&result.model);
+                result.trivial_reason = result.trivial_reason.map(|reason| {
+                    reason.expand(&var_map)
+                });
+                result
+            },
+        };
+        if result.clause_type == ClauseType::Satisfiable {
+            result.model.extend(forced_literals);
+        }
+        if config.verbosity >= 1 {
+            show_stats(&result);
+        }
+        if let (ClauseType::Unsatisfiable, Some(unassigned_system)) =
+               (&result.clause_type, unassigned_system) {
+            if let Some(core) = assumption_core(&unassigned_system, &assignment) {
+                let core = core.iter().map(|literal| literal.to_string()).collect::<Vec<_>>();
+                println!("c assumption_core={}", core.join(" "));
+            }
+        }
+        match result.into() {
             (ClauseType::Tautology, _)     => println!("TAUTOLOGY"),
-            (ClauseType::Satisfiable, interpretation) => println!("SATISFIABLE: {:?}", interpretation),
+            (ClauseType::Satisfiable, interpretation) => {
+                println!("SATISFIABLE: {}", format_model(&interpretation, model_format))
+            },
             (ClauseType::Unsatisfiable, _) => println!("UNSATISFIABLE"),
+            (ClauseType::Unknown, _)       => println!("UNKNOWN"),
         }
     }
 }
+
+#[test]
+fn test_add_assumptions() {
+    use rusat::dpll::basic_dpll;
+
+    // Simplifying (1)(¬1 ∨ 2) forces 1 and 2, leaving no clauses to contradict ¬1
+    let mut system = CNFSystem::new(None);
+    for literals in &[&[1][..], &[-1, 2]] {
+        system.add_clause(CNFClause::from_literals(literals).unwrap());
+    }
+    let unsimplified_system = system.clone();
+    let report = system.simplify();
+    assert!(system.is_empty());
+    let mut assumed_system = system.clone();
+    add_assumptions(&mut assumed_system, &[-1], &report.forced_literals);
+    assert_eq!(ClauseType::Unsatisfiable, basic_dpll(assumed_system).0);
+    assert_eq!(Some(vec![-1]), assumption_core(&unsimplified_system, &[-1]));
+
+    // An assumption that agrees with a forced literal leaves the system satisfiable
+    let mut assumed_system = system.clone();
+    add_assumptions(&mut assumed_system, &[1, 2], &report.forced_literals);
+    assert_eq!(ClauseType::Satisfiable, basic_dpll(assumed_system).0);
+}
+
+#[test]
+fn test_solve_files() {
+    use std::fs;
+
+    let directory = env::temp_dir().join(format!("rusat-solve-files-{}", process::id()));
+    fs::create_dir_all(directory.join("nested")).unwrap();
+    // The xz fixture is only added with the "xz" feature
+    #[cfg_attr(not(feature = "xz"), allow(unused_mut))]
+    let mut fixtures = vec![("sat.cnf", b"p cnf 2 2\n1 2 0\n-1 0\n".to_vec(), "SATISFIABLE"),
+                            ("nested/unsat.cnf", b"p cnf 1 2\n1 0\n-1 0\n".to_vec(),
+                             "UNSATISFIABLE"),
+                            ("nested/taut.cnf", b"p cnf 1 1\n1 -1 0\n".to_vec(), "TAUTOLOGY"),
+                            ("bad.cnf", b"1 x 0\n".to_vec(), "ERROR")];
+    #[cfg(feature = "xz")]
+    {
+        let mut compressed = vec![];
+        lzma_rs::xz_compress(&mut &b"p cnf 2 1\n-1 -2 0\n"[..], &mut compressed).unwrap();
+        fixtures.push(("nested/sat.cnf.xz", compressed, "SATISFIABLE"));
+    }
+    for &(name, ref contents, _) in &fixtures {
+        fs::write(directory.join(name), contents).unwrap();
+    }
+
+    let files = find_dimacs_files(&directory).unwrap();
+    let mut output = vec![];
+    solve_files(&files, &ParseOptions::default(), 1, &SolverConfig::default(), &mut output)
+        .unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let lines = output.lines().collect::<Vec<_>>();
+
+    // A line for each file, in order of path, with its verdict and how long it took
+    assert_eq!(files.len() + 5, lines.len());
+    for (line, file) in lines.iter().zip(&files) {
+        let fixture = fixtures.iter().find(|fixture| directory.join(fixture.0) == *file).unwrap();
+        let prefix = format!("{} {} ", file.display(), fixture.2);
+        assert!(line.starts_with(&prefix) && line.ends_with("ms"), "{}", line);
+    }
+    let count = |verdict| fixtures.iter().filter(|fixture| fixture.2 == verdict).count();
+    assert_eq!(vec![format!("c satisfiable={}", count("SATISFIABLE")),
+                    "c unsatisfiable=1".to_string(), "c tautology=1".to_string(),
+                    "c unknown=0".to_string(), "c error=1".to_string()],
+               lines[files.len()..]);
+}
@@ -0,0 +1,302 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::mpsc;
+use std::thread;
+use cnf_system::{CNFClause, CNFSystem, ClauseType};
+use dpll::{basic_dpll, basic_dpll_with_config, entails, SolverConfig};
+use rng::XorShift;
+
+/// An iterator over every model of a system, each given as a total assignment of the system's
+/// variables. After each model is found, a clause blocking it is added so that the next solve
+/// has to find a different one, until the system becomes Unsatisfiable.
+///
+/// Each model is usually found close to the last one, as the solver keeps trying the same
+/// polarities first. With `diversify` set, each solve first tries the opposite of the last
+/// model's polarities instead, which spreads the models out
+pub struct Models {
+    system: CNFSystem,
+    variables: BTreeSet<usize>,
+    diversify: bool,
+    phases: HashMap<usize, bool>,
+    finished: bool,
+}
+
+impl Models {
+    pub fn new(system: &CNFSystem, diversify: bool) -> Models {
+        Models {
+            system: system.clone(),
+            variables: system.variables(),
+            diversify,
+            // Keep to the system's own phases, as the blocking clauses would otherwise shift them
+            phases: system.initial_phases(),
+            finished: false,
+        }
+    }
+}
+
+impl Iterator for Models {
+    type Item = BTreeSet<isize>;
+
+    fn next(&mut self) -> Option<BTreeSet<isize>> {
+        if self.finished {
+            return None;
+        }
+        let config = SolverConfig{ phases: self.phases.clone(), ..SolverConfig::default() };
+        let result = basic_dpll_with_config(self.system.clone(), &config);
+        if result.clause_type != ClauseType::Satisfiable {
+            self.finished = true;
+            return None;
+        }
+        // The solver leaves alone the variables it doesn't need, so give them their phase
+        let model = self.variables.iter().map(|&variable| {
+            let literal = variable as isize;
+            if result.model.contains(&literal) {
+                literal
+            } else if result.model.contains(&-literal) {
+                -literal
+            } else if self.phases[&variable] {
+                literal
+            } else {
+                -literal
+            }
+        }).collect::<BTreeSet<isize>>();
+
+        let mut blocking_clause = CNFClause::new();
+        for &literal in &model {
+            blocking_clause.add(-literal);
+        }
+        self.system.add_clause(blocking_clause);
+        if self.diversify {
+            self.phases = model.iter()
+                               .map(|literal| (literal.unsigned_abs(), *literal < 0))
+                               .collect();
+        }
+        Some(model)
+    }
+}
+
+/// Returns every model of the system. See `Models`
+pub fn all_models(system: &CNFSystem, diversify: bool) -> Vec<BTreeSet<isize>> {
+    Models::new(system, diversify).collect()
+}
+
+/// Returns up to `count` models of the system, fewer if it doesn't have that many. The search
+/// stops as soon as enough are found, so this is much cheaper than `all_models` when only a few
+/// are needed. See `Models`
+pub fn models_up_to(system: &CNFSystem, count: usize) -> Vec<BTreeSet<isize>> {
+    Models::new(system, false).take(count).collect()
+}
+
+/// Returns `n` models of the system picked at random, which can repeat, or none if it's
+/// Unsatisfiable. The same seed always gives the same models.
+///
+/// Each model is built by going through the variables in a random order and giving each one a
+/// random value, unless no model is left with that value, in which case it gets the other one.
+/// So every model is possible, with a chance of at least 1/2^(variable count), but the sampling is
+/// only roughly uniform: a model's chance halves at every variable where both values were still
+/// possible, however many models each value had left. E.g. (1 ∨ 2) gives {1, 2} a quarter of the
+/// time rather than a third. Checking a value takes a solve, so each model takes one solve per
+/// variable
+pub fn sample_models(system: &CNFSystem, n: usize, seed: u64) -> Vec<BTreeSet<isize>> {
+    if n == 0 || basic_dpll(system.clone()).0 != ClauseType::Satisfiable {
+        return vec![];
+    }
+    let mut rng = XorShift::new(seed);
+    let variables = system.variables().into_iter().collect::<Vec<_>>();
+    (0..n).map(|_| {
+        let mut order = variables.clone();
+        rng.shuffle(&mut order);
+        let mut fixed_system = system.clone();
+        order.into_iter().map(|variable| {
+            let literal = if rng.below(2) == 0 { variable as isize } else { -(variable as isize) };
+            // If every model left has the other value, the system entails it
+            let literal = if entails(&fixed_system, -literal) { -literal } else { literal };
+            fixed_system.propagate(literal);
+            literal
+        }).collect()
+    }).collect()
+}
+
+/// Enumerates the models of the system on another thread, sending each one over the returned
+/// channel as it's found, which closes once there are no more. The channel holds no models
+/// waiting to be received, so the next one is only searched for once the last one has been
+/// taken. Dropping the receiver stops the enumeration. See `Models`
+pub fn stream_models(system: CNFSystem) -> mpsc::Receiver<BTreeSet<isize>> {
+    let (sender, receiver) = mpsc::sync_channel(0);
+    thread::spawn(move || {
+        for each_model in Models::new(&system, false) {
+            if sender.send(each_model).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// Returns a model of the system that is minimal in the variables it makes true: no model makes
+/// only some of them true. None if the system is Unsatisfiable.
+///
+/// Starting from any model, it keeps looking for one whose true variables are a strict subset of
+/// the last one's, i.e. that keeps every false variable false and makes at least one of the true
+/// ones false, until there's none
+pub fn minimal_model(system: &CNFSystem) -> Option<BTreeSet<isize>> {
+    let variables = system.variables();
+    // Trying every variable as false first tends to find small models straight away
+    let phases = variables.iter().map(|&variable| (variable, false)).collect();
+    let config = SolverConfig{ phases, ..SolverConfig::default() };
+    let mut model = None;
+    let mut shrunk_system = system.clone();
+    loop {
+        let result = basic_dpll_with_config(shrunk_system.clone(), &config);
+        if result.clause_type != ClauseType::Satisfiable {
+            return model;
+        }
+        // Any variable the solver didn't need can be false
+        let current_model = variables.iter().map(|&variable| {
+            let literal = variable as isize;
+            if result.model.contains(&literal) { literal } else { -literal }
+        }).collect::<BTreeSet<isize>>();
+        let mut shrinking_clause = CNFClause::new();
+        for &literal in &current_model {
+            if literal > 0 {
+                shrinking_clause.add(-literal);
+            } else {
+                let mut unit = CNFClause::new();
+                unit.add(literal);
+                shrunk_system.add_clause(unit);
+            }
+        }
+        let fully_shrunk = shrinking_clause.is_empty();
+        model = Some(current_model);
+        if fully_shrunk {
+            return model;
+        }
+        shrunk_system.add_clause(shrinking_clause);
+    }
+}
+
+#[test]
+fn test_all_models() {
+    use brute_force::Assignments;
+    use testutil::{XorShift, random_system, satisfies, system_from};
+
+    let mut rng = XorShift::new(143);
+    for _ in 0..30 {
+        let system = random_system(&mut rng, 6, 12, 3);
+        let expected = Assignments::new(&system.variables())
+            .filter(|assignment| satisfies(&system, assignment))
+            .collect::<BTreeSet<_>>();
+        for &diversify in &[false, true] {
+            let models = all_models(&system, diversify);
+            assert_eq!(expected.len(), models.len());
+            assert_eq!(expected, models.into_iter().collect::<BTreeSet<_>>());
+        }
+    }
+
+    assert_eq!(vec![BTreeSet::new()], all_models(&CNFSystem::new(None), false));
+    assert!(all_models(&system_from(&[&[1], &[-1]]), false).is_empty());
+}
+
+#[test]
+fn test_all_models_diversify() {
+    use dpll::model_diff;
+    use testutil::system_from;
+
+    // Every model of (1 ∨ 2)(3 ∨ 4)...(11 ∨ 12) only has to set one variable of each clause
+    let clauses = (0..6).map(|pair| [2 * pair + 1, 2 * pair + 2]).collect::<Vec<_>>();
+    let system = system_from(&clauses.iter().map(|clause| &clause[..]).collect::<Vec<_>>());
+    let spread = |diversify: bool| {
+        let models = Models::new(&system, diversify).take(5).collect::<Vec<_>>();
+        models.windows(2).map(|pair| model_diff(&pair[0], &pair[1]).len()).sum::<usize>()
+    };
+    assert!(spread(true) > spread(false));
+}
+
+#[test]
+fn test_models_up_to() {
+    use testutil::{satisfies, system_from};
+
+    // (1 ∨ 2) has three models
+    let system = system_from(&[&[1, 2]]);
+    let models = models_up_to(&system, 2);
+    assert_eq!(2, models.len());
+    assert_ne!(models[0], models[1]);
+    assert!(models.iter().all(|model| satisfies(&system, model)));
+
+    assert_eq!(all_models(&system, false), models_up_to(&system, 5));
+    assert!(models_up_to(&system, 0).is_empty());
+    assert!(models_up_to(&system_from(&[&[1], &[-1]]), 3).is_empty());
+}
+
+#[test]
+fn test_sample_models() {
+    use testutil::{random_system, satisfies, system_from};
+
+    // Each of the three models of (1 ∨ 2) comes up
+    let system = system_from(&[&[1, 2]]);
+    let samples = sample_models(&system, 200, 200);
+    assert_eq!(200, samples.len());
+    let sampled = samples.iter().cloned().collect::<BTreeSet<_>>();
+    assert_eq!(all_models(&system, false).into_iter().collect::<BTreeSet<_>>(), sampled);
+    assert_eq!(samples, sample_models(&system, 200, 200));
+
+    let mut rng = XorShift::new(200);
+    for _ in 0..10 {
+        let system = random_system(&mut rng, 8, 24, 3);
+        let samples = sample_models(&system, 5, rng.next_u64());
+        assert!(samples.iter().all(|model| {
+            satisfies(&system, model) && model.len() == system.variables().len()
+        }));
+    }
+
+    assert!(sample_models(&system_from(&[&[1], &[-1]]), 10, 1).is_empty());
+    assert!(sample_models(&system, 0, 1).is_empty());
+}
+
+#[test]
+fn test_minimal_model() {
+    use brute_force::Assignments;
+    use testutil::{XorShift, pigeonhole, random_system, satisfies, system_from};
+
+    // (a ∨ b) has models with one or both true, but only the former are minimal
+    let model = minimal_model(&system_from(&[&[1, 2]])).unwrap();
+    assert_eq!(1, model.iter().filter(|&&literal| literal > 0).count());
+    assert_eq!(2, model.len());
+
+    assert_eq!(Some(BTreeSet::new()), minimal_model(&CNFSystem::new(None)));
+    assert_eq!(None, minimal_model(&pigeonhole(2)));
+
+    // No model of the system makes a strict subset of the minimal model's variables true
+    let true_variables = |model: &BTreeSet<isize>| {
+        model.iter().filter(|&&literal| literal > 0).cloned().collect::<BTreeSet<_>>()
+    };
+    let mut rng = XorShift::new(162);
+    for _ in 0..30 {
+        let system = random_system(&mut rng, 8, 20, 3);
+        let model = match minimal_model(&system) {
+            Some(model) => model,
+            None        => continue,
+        };
+        assert!(satisfies(&system, &model));
+        let minimal = true_variables(&model);
+        assert!(!Assignments::new(&system.variables()).any(|assignment| {
+            let smaller = true_variables(&assignment);
+            satisfies(&system, &assignment) && smaller != minimal && smaller.is_subset(&minimal)
+        }));
+    }
+}
+
+#[test]
+fn test_stream_models() {
+    use testutil::system_from;
+
+    let system = system_from(&[&[1, 2], &[-1, 3]]);
+    let models = stream_models(system.clone()).iter().collect::<BTreeSet<_>>();
+    assert_eq!(all_models(&system, false).into_iter().collect::<BTreeSet<_>>(), models);
+    assert_eq!(4, models.len());
+    assert_eq!(0, stream_models(system_from(&[&[1], &[-1]])).iter().count());
+
+    // Taking only some of the models leaves the rest unsearched
+    let receiver = stream_models(system);
+    assert!(receiver.recv().is_ok());
+    drop(receiver);
+}
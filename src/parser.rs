@@ -0,0 +1,1107 @@
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::num::IntErrorKind;
+use std::path::{Path, PathBuf};
+use cnf_system::{AddOutcome, CNFSystem, CNFClause};
+use var_map::VarMap;
+
+/// The largest variable accepted by default, which is the largest that fits in a 32 bit literal
+pub const DEFAULT_MAX_VARIABLE: usize = i32::MAX as usize;
+
+/// The most clauses room is made for up front because of a problem line, so that a huge declared
+/// count can't allocate memory the input never fills
+const MAX_RESERVED_CLAUSES: usize = 1 << 20;
+
+/// Options that change how strictly the DIMACS input is read
+#[derive(Clone, Debug)]
+pub struct ParseOptions {
+    /// Report input that is accepted but isn't strictly valid DIMACS, and treat a '%' line as
+    /// invalid rather than as the end of the input
+    pub strict: bool,
+    /// Keep the comment lines in `CNFSystem::comments` so that `write_dimacs` can write them back
+    pub keep_comments: bool,
+    /// Fill in `ParsedSystem::clause_numbers`, so that a clause can be referred to by its place
+    /// in the input, e.g. for `--verify`
+    pub keep_clause_numbers: bool,
+    /// Keep tautological clauses in the system instead of leaving them out, so that the system is
+    /// exactly the input. See `CNFSystem::remove_tautologies`
+    pub keep_tautologies: bool,
+    /// Reject any literal whose variable is larger than this
+    pub max_variable: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            strict: false,
+            keep_comments: false,
+            keep_clause_numbers: false,
+            keep_tautologies: false,
+            max_variable: DEFAULT_MAX_VARIABLE,
+        }
+    }
+}
+
+/// The counts declared by a problem line i.e. 'p cnf VARIABLES CLAUSES'
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProblemHeader {
+    pub vars: usize,
+    pub clauses: usize,
+}
+
+/// A system read in from DIMACS input
+#[derive(Clone, Debug)]
+pub struct ParsedSystem {
+    pub system: CNFSystem,
+    /// The counts of the input's problem line, if it has one that could be read. Room for the
+    /// declared clauses is made up front, and under `ParseOptions::strict` counts that don't match
+    /// the input are reported in `warnings`
+    pub header: Option<ProblemHeader>,
+    /// The units that exist in the input system, before any algorithm is applied to it
+    pub units: HashSet<isize>,
+    /// True if the input has at least one tautological clause, which is left out of the system
+    /// unless `ParseOptions::keep_tautologies` is set
+    pub contains_tautologies: bool,
+    /// The amount of clauses read that were the same as an earlier one, up to the order of their
+    /// literals, and so merged into it
+    pub duplicate_clauses: usize,
+    /// Gives each variable of the input an index, in the order they were first read
+    pub var_map: VarMap,
+    /// Problems with the input that weren't bad enough to stop parsing. Only filled in when
+    /// `ParseOptions::strict` is set
+    pub warnings: Vec<String>,
+    /// The literals listed on 'a' lines, to solve the system under as if fixed as true
+    pub assumptions: Vec<isize>,
+    /// The number of each clause of the system in the input, counting from 1, or the number of
+    /// the first if it was read more than once. Only filled in when
+    /// `ParseOptions::keep_clause_numbers` is set
+    pub clause_numbers: HashMap<CNFClause, usize>,
+}
+
+impl ParsedSystem {
+    /// Returns the number in the input of the first clause that the model doesn't satisfy, or
+    /// None if it satisfies them all. Clauses without a number are passed over, so unless
+    /// `ParseOptions::keep_clause_numbers` was set this is always None; check the system with
+    /// `CNFSystem::unsatisfied_clause` then instead
+    pub fn first_unsatisfied_clause(&self, model: &BTreeSet<isize>) -> Option<usize> {
+        self.system.clauses.iter()
+                           .filter(|clause| !clause.is_satisfied_by(model))
+                           .filter_map(|clause| self.clause_numbers.get(clause).cloned())
+                           .min()
+    }
+}
+
+/// An incremental problem read from ICNF input, e.g. for `--format icnf`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IncrementalProblem {
+    /// Every clause, in the order they were read
+    pub clauses: Vec<CNFClause>,
+    pub queries: Vec<IncrementalQuery>,
+}
+
+/// A query of an ICNF input, i.e. an 'a' line, which asks to solve the clauses read before it
+/// under its assumptions
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IncrementalQuery {
+    /// The amount of clauses read before the query, so the clauses to solve are
+    /// `IncrementalProblem::clauses[..clause_count]`
+    pub clause_count: usize,
+    pub assumptions: Vec<isize>,
+}
+
+#[derive(Debug)]
+pub enum ParseError {
+    Io(io::Error),
+    /// A line that isn't a comment, a problem line or a list of literals
+    InvalidLine(String),
+    /// A literal whose variable is larger than `ParseOptions::max_variable`, or too large to read
+    VariableTooLarge{ literal: String, max_variable: usize },
+    /// A word made of digits and signs that isn't a literal, e.g. '--3', '3-' or a signed zero
+    /// such as '-0', which would otherwise be read as the end of a clause
+    InvalidLiteral{ literal: String, line: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::Io(ref error) => write!(f, "{}", error),
+            ParseError::InvalidLine(ref line) => {
+                write!(f, "not a valid comment, program or input line: {}", line)
+            },
+            ParseError::VariableTooLarge{ ref literal, max_variable } => {
+                write!(f, "variable of literal {} is larger than the maximum of {}",
+                       literal, max_variable)
+            },
+            ParseError::InvalidLiteral{ ref literal, ref line } => {
+                write!(f, "not a valid literal: {} in line: {}", literal, line)
+            },
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(error: io::Error) -> ParseError {
+        ParseError::Io(error)
+    }
+}
+
+/// The magic bytes that start a gzip and an xz stream
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const XZ_MAGIC: &[u8] = &[0xfd, b'7', b'z', b'X', b'Z', 0x00];
+
+/// Wraps the input in a decompressor if it starts with the magic bytes of a gzip or xz stream, so
+/// that compressed benchmarks can be handed straight to `parse_dimacs`. Any other input is passed
+/// through as it is. Each format needs its feature ("gzip" or "xz") enabled, otherwise it's an
+/// error.
+pub fn decompress<'a, R: BufRead + 'a>(mut input: R) -> io::Result<Box<dyn BufRead + 'a>> {
+    let start = input.fill_buf()?;
+    if start.starts_with(GZIP_MAGIC) {
+        gzip_decoder(input)
+    } else if start.starts_with(XZ_MAGIC) {
+        xz_decoder(input)
+    } else {
+        Ok(Box::new(input))
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gzip_decoder<'a, R: BufRead + 'a>(input: R) -> io::Result<Box<dyn BufRead + 'a>> {
+    Ok(Box::new(io::BufReader::new(flate2::bufread::MultiGzDecoder::new(input))))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip_decoder<'a, R: BufRead + 'a>(_input: R) -> io::Result<Box<dyn BufRead + 'a>> {
+    Err(unsupported_compression("gzip"))
+}
+
+// lzma-rs only decompresses a whole stream at once, so the output is held in memory
+#[cfg(feature = "xz")]
+fn xz_decoder<'a, R: BufRead + 'a>(mut input: R) -> io::Result<Box<dyn BufRead + 'a>> {
+    let mut output = vec![];
+    lzma_rs::xz_decompress(&mut input, &mut output).map_err(|error| match error {
+        lzma_rs::error::Error::IoError(error) => error,
+        error => io::Error::new(io::ErrorKind::InvalidData, error.to_string()),
+    })?;
+    Ok(Box::new(io::Cursor::new(output)))
+}
+
+#[cfg(not(feature = "xz"))]
+fn xz_decoder<'a, R: BufRead + 'a>(_input: R) -> io::Result<Box<dyn BufRead + 'a>> {
+    Err(unsupported_compression("xz"))
+}
+
+#[cfg(not(all(feature = "gzip", feature = "xz")))]
+fn unsupported_compression(format: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData,
+                   format!("the input is {} compressed, but {} support isn't built in", format,
+                           format))
+}
+
+/// The endings of the file names `find_dimacs_files` picks out
+const DIMACS_EXTENSIONS: &[&str] = &[".cnf", ".cnf.gz", ".cnf.xz"];
+
+/// Returns every DIMACS file in the directory and its subdirectories, i.e. every file whose name
+/// ends in '.cnf', or '.cnf.gz' or '.cnf.xz' if it's compressed, sorted by path
+pub fn find_dimacs_files(directory: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut directories = vec![directory.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        for each_entry in fs::read_dir(&directory)? {
+            let each_entry = each_entry?;
+            let path = each_entry.path();
+            let is_dimacs = path.file_name().and_then(|name| name.to_str()).is_some_and(|name| {
+                DIMACS_EXTENSIONS.iter().any(|extension| name.ends_with(extension))
+            });
+            // Symlinks aren't followed into directories, so that a loop of them can't trap the walk
+            if each_entry.file_type()?.is_dir() {
+                directories.push(path);
+            } else if is_dimacs {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Reads a system in DIMACS form. Each clause is a list of literals terminated by a 0 and may be
+/// spread over several lines. Tautologies are left out of the system, as they're always true,
+/// unless `keep_tautologies` is set.
+/// A clause that is still open at the end of the input is kept, as if it had been terminated.
+/// Unless `strict` is set, a line starting with '%' ends the input, as in the SATLIB benchmarks
+/// which follow it with a stray 0.
+///
+/// A literal is an optional sign followed by decimal digits, e.g. `3`, `-3` or `+3`. The '+'
+/// sign isn't canonical DIMACS, so it's an invalid line under `strict`.
+///
+/// Outside of `strict`, a line like 'a 3 -5 0' lists literals to assume rather than a clause,
+/// which are kept in order in `ParsedSystem::assumptions`.
+pub fn parse_dimacs<R: BufRead>(input: R, options: &ParseOptions)
+                                -> Result<ParsedSystem, ParseError> {
+    parse_dimacs_lines(input.lines().map(|line| line.map(Cow::Owned)), options)
+}
+
+/// Reads a system in DIMACS form straight from bytes in memory, as `parse_dimacs` does. The input
+/// is taken to be ASCII: each line is borrowed from the bytes rather than copied, and any bytes
+/// that aren't valid UTF-8, e.g. in a comment, are replaced instead of being an error
+pub fn parse_dimacs_bytes(input: &[u8], options: &ParseOptions)
+                          -> Result<ParsedSystem, ParseError> {
+    let lines = input.split(|&byte| byte == b'\n').map(|line| {
+        let line = if line.last() == Some(&b'\r') { &line[..line.len() - 1] } else { line };
+        Ok(String::from_utf8_lossy(line))
+    });
+    parse_dimacs_lines(lines, options)
+}
+
+/// Reads the lines of DIMACS input for `parse_dimacs` and `parse_dimacs_bytes`
+fn parse_dimacs_lines<'a, I>(lines: I, options: &ParseOptions) -> Result<ParsedSystem, ParseError>
+        where I: Iterator<Item = io::Result<Cow<'a, str>>> {
+    let mut parsed = ParsedSystem {
+        system: CNFSystem::new(None),
+        header: None,
+        units: HashSet::new(),
+        contains_tautologies: false,
+        duplicate_clauses: 0,
+        var_map: VarMap::new(),
+        warnings: vec![],
+        assumptions: vec![],
+        clause_numbers: HashMap::new(),
+    };
+    let mut current_clause = CNFClause::new();
+    // Whether any literal has been read since the last terminating 0
+    let mut clause_pending = false;
+    // The amount of clauses and the largest variable read, to check against the problem line
+    let mut clauses_read = 0;
+    let mut largest_variable = 0;
+
+    for each_line in lines {
+        let current_line = each_line?;
+        let words = current_line.split_whitespace().collect::<Vec<_>>();
+        // Skip all comment lines i.e. a line that begins with 'c', and read the counts of the
+        // program line i.e. a line like 'p cnf VARIABLE_COUNT CLAUSE_COUNT'
+        match words.first().and_then(|first_word| first_word.chars().next()) {
+            Some('c') => {
+                if options.keep_comments {
+                    parsed.system.comments.push(comment_text(&current_line).to_string());
+                }
+                continue;
+            },
+            Some('p') | Some('P') => {
+                match parse_problem_line(&words, options.strict) {
+                    Some(header) => {
+                        parsed.system.clauses.reserve(
+                            header.clauses.min(MAX_RESERVED_CLAUSES));
+                        parsed.header = Some(header);
+                    },
+                    None if options.strict => {
+                        return Err(ParseError::InvalidLine(current_line.to_string()));
+                    },
+                    None => {},
+                }
+                continue;
+            },
+            Some('a') if words[0] == "a" && !options.strict => {
+                parse_assumption_line(&mut parsed, &words[1..], &current_line, options)?;
+                continue;
+            },
+            None => continue,
+            Some('%') if !options.strict => break,
+            Some(_) => {},
+        }
+        // Now, insert the actual input into the system
+        for each_word in words {
+            let literal = parse_literal(each_word, &current_line, options)?;
+            if literal == 0 {
+                clauses_read += 1;
+                finish_clause(&mut parsed, &mut current_clause, clauses_read, options);
+                clause_pending = false;
+            } else {
+                current_clause.add(literal);
+                largest_variable = largest_variable.max(literal.unsigned_abs());
+                parsed.var_map.index_of(literal.unsigned_abs());
+                clause_pending = true;
+            }
+        }
+    }
+
+    if clause_pending {
+        if options.strict {
+            parsed.warnings.push("last clause is not terminated by a 0".to_string());
+        }
+        clauses_read += 1;
+        finish_clause(&mut parsed, &mut current_clause, clauses_read, options);
+    }
+    if let (true, Some(header)) = (options.strict, parsed.header) {
+        if clauses_read != header.clauses {
+            let mut warning = format!("the problem line declares {} {} but {} {} read",
+                                      header.clauses,
+                                      if header.clauses == 1 { "clause" } else { "clauses" },
+                                      clauses_read, if clauses_read == 1 { "was" } else { "were" });
+            if parsed.duplicate_clauses > 0 {
+                warning += &format!(", {} of them duplicates", parsed.duplicate_clauses);
+            }
+            parsed.warnings.push(warning);
+        }
+        if largest_variable > header.vars {
+            parsed.warnings.push(format!("the problem line declares {} variables but variable {} \
+                                          was read", header.vars,
+                                         largest_variable));
+        }
+    }
+    Ok(parsed)
+}
+
+/// Reads input in the ICNF format for incremental solving: a 'p inccnf' line, then clauses as in
+/// DIMACS mixed with 'a' lines like 'a 3 -5 0', each asking to solve the clauses so far under the
+/// literals it lists. Unless `strict` is set, any case of the problem line is accepted. Any other
+/// problem line is invalid, as is an 'a' line that comes while a clause is still open
+pub fn parse_icnf<R: BufRead>(input: R, options: &ParseOptions)
+                              -> Result<IncrementalProblem, ParseError> {
+    let mut problem = IncrementalProblem::default();
+    let mut current_clause = CNFClause::new();
+    let mut clause_pending = false;
+    for each_line in input.lines() {
+        let current_line = each_line?;
+        let words = current_line.split_whitespace().collect::<Vec<_>>();
+        match words.first().and_then(|first_word| first_word.chars().next()) {
+            None | Some('c') => continue,
+            Some('p') | Some('P') => {
+                let is_problem_line = match *words {
+                    ["p", "inccnf"] => true,
+                    [p, inccnf, ..] if !options.strict => {
+                        p.eq_ignore_ascii_case("p") && inccnf.eq_ignore_ascii_case("inccnf")
+                    },
+                    _ => false,
+                };
+                if !is_problem_line {
+                    return Err(ParseError::InvalidLine(current_line.clone()));
+                }
+                continue;
+            },
+            Some('a') if words[0] == "a" => {
+                if clause_pending {
+                    return Err(ParseError::InvalidLine(current_line.clone()));
+                }
+                let mut query = IncrementalQuery{ clause_count: problem.clauses.len(),
+                                                  assumptions: vec![] };
+                for (index, each_word) in words[1..].iter().enumerate() {
+                    match parse_literal(each_word, &current_line, options)? {
+                        0 if index + 2 < words.len() => {
+                            return Err(ParseError::InvalidLine(current_line.clone()));
+                        },
+                        0       => {},
+                        literal => query.assumptions.push(literal),
+                    }
+                }
+                problem.queries.push(query);
+                continue;
+            },
+            Some(_) => {},
+        }
+        for each_word in words {
+            match parse_literal(each_word, &current_line, options)? {
+                0 => {
+                    problem.clauses.push(::std::mem::replace(&mut current_clause,
+                                                             CNFClause::new()));
+                    clause_pending = false;
+                },
+                literal => {
+                    current_clause.add(literal);
+                    clause_pending = true;
+                },
+            }
+        }
+    }
+    if clause_pending {
+        problem.clauses.push(current_clause);
+    }
+    Ok(problem)
+}
+
+/// Reads a list of literals to fix as true, separated by whitespace over any amount of lines,
+/// e.g. for `--assign`. As in parse_dimacs(), lines starting with 'c' are comments and a 0 ends
+/// the list, so anything but comments after it is an invalid line
+pub fn parse_assignment<R: BufRead>(input: R, options: &ParseOptions)
+                                    -> Result<Vec<isize>, ParseError> {
+    let mut literals = vec![];
+    let mut terminated = false;
+    for each_line in input.lines() {
+        let current_line = each_line?;
+        let words = current_line.split_whitespace().collect::<Vec<_>>();
+        match words.first() {
+            None => continue,
+            Some(first_word) if first_word.starts_with('c') => continue,
+            Some(_) if terminated => return Err(ParseError::InvalidLine(current_line.clone())),
+            Some(_) => {},
+        }
+        for each_word in words {
+            if terminated {
+                return Err(ParseError::InvalidLine(current_line.clone()));
+            }
+            match parse_literal(each_word, &current_line, options)? {
+                0       => terminated = true,
+                literal => literals.push(literal),
+            }
+        }
+    }
+    Ok(literals)
+}
+
+/// Reads a model from a solver's output in the SAT competition format, i.e. 'v' lines of literals
+/// ended by a 0, e.g. for `--verify`. Comment lines and the 's' status line are skipped, as is
+/// anything after the 0, and any other line is invalid
+pub fn parse_solution<R: BufRead>(input: R, options: &ParseOptions)
+                                  -> Result<BTreeSet<isize>, ParseError> {
+    let mut model = BTreeSet::new();
+    for each_line in input.lines() {
+        let current_line = each_line?;
+        let words = current_line.split_whitespace().collect::<Vec<_>>();
+        match words.first() {
+            None => continue,
+            Some(&"v") => {},
+            Some(first_word) if first_word.starts_with('c') || *first_word == "s" => continue,
+            Some(_) => return Err(ParseError::InvalidLine(current_line.clone())),
+        }
+        for each_word in &words[1..] {
+            match parse_literal(each_word, &current_line, options)? {
+                0       => return Ok(model),
+                literal => { model.insert(literal); },
+            }
+        }
+    }
+    Ok(model)
+}
+
+/// Reads the literals of an assumption line i.e. a line like 'a 3 -5 0', where `words` are the
+/// words after the 'a'
+fn parse_assumption_line(parsed: &mut ParsedSystem, words: &[&str], line: &str,
+                         options: &ParseOptions) -> Result<(), ParseError> {
+    for (index, each_word) in words.iter().enumerate() {
+        match parse_literal(each_word, line, options)? {
+            0 if index + 1 < words.len() => return Err(ParseError::InvalidLine(line.to_string())),
+            0       => {},
+            literal => {
+                parsed.var_map.index_of(literal.unsigned_abs());
+                parsed.assumptions.push(literal);
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Reads one literal of `line`, or a terminating 0
+fn parse_literal(word: &str, line: &str, options: &ParseOptions) -> Result<isize, ParseError> {
+    if options.strict && word.starts_with('+') {
+        return Err(ParseError::InvalidLine(line.to_string()));
+    }
+    let invalid_literal = || ParseError::InvalidLiteral{ literal: word.to_string(),
+                                                         line: line.to_string() };
+    let literal = match word.parse::<isize>() {
+        // Only an unsigned 0 ends a clause
+        Ok(0) if word.starts_with(['-', '+']) => return Err(invalid_literal()),
+        Ok(literal) => literal,
+        Err(error) => return Err(match *error.kind() {
+            IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => too_large(word, options),
+            _ if word.chars().all(|c| c.is_ascii_digit() || c == '-' || c == '+') => {
+                invalid_literal()
+            },
+            _ => ParseError::InvalidLine(line.to_string()),
+        }),
+    };
+    if literal.unsigned_abs() > options.max_variable {
+        return Err(too_large(word, options));
+    }
+    Ok(literal)
+}
+
+/// Reads the counts of a problem line. Unless `strict` is set, any case of 'p cnf' is accepted
+/// and the counts are the first two numbers after it, whatever else is on the line. Under `strict`
+/// it has to be exactly 'p cnf VARIABLE_COUNT CLAUSE_COUNT'
+fn parse_problem_line(words: &[&str], strict: bool) -> Option<ProblemHeader> {
+    let counts = if strict {
+        match *words {
+            ["p", "cnf", variable_count, clause_count] => {
+                vec![variable_count.parse().ok()?, clause_count.parse().ok()?]
+            },
+            _ => return None,
+        }
+    } else {
+        if words.len() < 2 || !words[0].eq_ignore_ascii_case("p")
+                           || !words[1].eq_ignore_ascii_case("cnf") {
+            return None;
+        }
+        words[2..].iter().filter_map(|word| word.parse::<usize>().ok()).take(2).collect()
+    };
+    match counts[..] {
+        [vars, clauses] => Some(ProblemHeader{ vars, clauses }),
+        _ => None,
+    }
+}
+
+fn too_large(literal: &str, options: &ParseOptions) -> ParseError {
+    ParseError::VariableTooLarge{ literal: literal.to_string(), max_variable: options.max_variable }
+}
+
+/// Returns the text of a comment line, i.e. everything after the 'c' and the space following it
+fn comment_text(line: &str) -> &str {
+    let text = &line.trim_start()[1..];
+    text.strip_prefix(' ').unwrap_or(text)
+}
+
+/// Writes the system out in DIMACS form, starting with its comments and the problem line. The
+/// clauses are written in order, so the same system always gives the same output
+pub fn write_dimacs<W: Write>(system: &CNFSystem, output: &mut W) -> io::Result<()> {
+    for each_comment in &system.comments {
+        if each_comment.is_empty() {
+            writeln!(output, "c")?;
+        } else {
+            writeln!(output, "c {}", each_comment)?;
+        }
+    }
+    let variable_count = system.variables().last().cloned().unwrap_or(0);
+    writeln!(output, "p cnf {} {}", variable_count, system.len())?;
+    let mut clauses = system.clauses.iter().collect::<Vec<_>>();
+    clauses.sort();
+    for each_clause in clauses {
+        for literal in each_clause.iter() {
+            write!(output, "{} ", literal)?;
+        }
+        writeln!(output, "0")?;
+    }
+    Ok(())
+}
+
+/// The ways a model can be written out
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModelFormat {
+    /// The set of true literals, e.g. `{-1, 2}`
+    Signed,
+    /// The value of each variable in order, e.g. `1=false 2=true`
+    Assign,
+}
+
+/// Returns the model written out in the given format
+pub fn format_model(model: &BTreeSet<isize>, format: ModelFormat) -> String {
+    match format {
+        ModelFormat::Signed => format!("{:?}", model),
+        ModelFormat::Assign => {
+            let mut literals = model.iter().cloned().collect::<Vec<_>>();
+            literals.sort_by_key(|literal| literal.unsigned_abs());
+            literals.iter()
+                    .map(|literal| format!("{}={}", literal.unsigned_abs(), *literal > 0))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+        },
+    }
+}
+
+/// Adds the clause that has just been read, the `number`th of the input, to the system, leaving
+/// `clause` empty for the next one
+fn finish_clause(parsed: &mut ParsedSystem, clause: &mut CNFClause, number: usize,
+                 options: &ParseOptions) {
+    let finished_clause = ::std::mem::replace(clause, CNFClause::new());
+    if options.keep_clause_numbers {
+        parsed.clause_numbers.entry(finished_clause.clone()).or_insert(number);
+    }
+    // Check for tautologies
+    if finished_clause.is_tautology() {
+        parsed.contains_tautologies = true;
+        if options.keep_tautologies && !parsed.system.add_clause(finished_clause) {
+            parsed.duplicate_clauses += 1;
+        }
+    } else if !finished_clause.is_empty() {
+        match parsed.system.add_clause_reporting(finished_clause) {
+            AddOutcome::Unit(unit) => {
+                parsed.units.insert(unit);
+            },
+            AddOutcome::Duplicate => parsed.duplicate_clauses += 1,
+            _ => {},
+        }
+    }
+}
+
+#[test]
+fn test_parse_dimacs() {
+    let input = "c A comment\np cnf 4 3\n1 -2 0\n2 3\n-4 0\n2 -2 4 0\n";
+    let parsed = parse_dimacs(input.as_bytes(), &ParseOptions::default()).unwrap();
+
+    // A clause spanning two lines is one clause, and the tautology is left out
+    let mut clause = CNFClause::new();
+    clause.add(1);
+    clause.add(-2);
+    let mut clause2 = CNFClause::new();
+    clause2.add(2);
+    clause2.add(3);
+    clause2.add(-4);
+    assert_eq!(2, parsed.system.len());
+    assert!(parsed.system.clauses.contains(&clause));
+    assert!(parsed.system.clauses.contains(&clause2));
+    assert!(parsed.contains_tautologies);
+    assert!(parsed.units.is_empty());
+    assert!(parsed.warnings.is_empty());
+
+    assert!(parse_dimacs("1 2 0\n1 x 0\n".as_bytes(), &ParseOptions::default()).is_err());
+}
+
+#[test]
+fn test_parse_dimacs_bytes() {
+    let input = "c A comment\r\np cnf 4 4\r\n1 -2 0\n2 3\n-4 0\n-1 0\n2 -2 4 0";
+    let options = ParseOptions{ keep_comments: true, ..ParseOptions::default() };
+    let from_reader = parse_dimacs(input.as_bytes(), &options).unwrap();
+    let from_bytes = parse_dimacs_bytes(input.as_bytes(), &options).unwrap();
+    assert_eq!(from_reader.system, from_bytes.system);
+    assert_eq!(vec!["A comment".to_string()], from_bytes.system.comments);
+    assert_eq!(from_reader.units, from_bytes.units);
+    assert_eq!(from_reader.header, from_bytes.header);
+    assert!(from_bytes.contains_tautologies);
+
+    let (system, units) = CNFSystem::from_bytes(input.as_bytes()).unwrap();
+    assert_eq!(from_reader.system.clauses, system.clauses);
+    assert_eq!(from_reader.units, units);
+
+    // Bytes that aren't UTF-8 only matter to the reader
+    let input = b"c caf\xe9\n1 2 0\n";
+    assert!(parse_dimacs(&input[..], &ParseOptions::default()).is_err());
+    assert_eq!(1, CNFSystem::from_bytes(input).unwrap().0.len());
+    assert!(CNFSystem::from_bytes(b"1 x 0\n").is_err());
+}
+
+#[test]
+fn test_parse_dimacs_unterminated_last_clause() {
+    let input = "p cnf 4 2\n1 -2 0\n3 4\n";
+    let mut last_clause = CNFClause::new();
+    last_clause.add(3);
+    last_clause.add(4);
+
+    let parsed = parse_dimacs(input.as_bytes(), &ParseOptions::default()).unwrap();
+    assert_eq!(2, parsed.system.len());
+    assert!(parsed.system.clauses.contains(&last_clause));
+    assert!(parsed.warnings.is_empty());
+
+    // The clause is still kept under --strict, but it's reported
+    let strict = ParseOptions{ strict: true, ..ParseOptions::default() };
+    let parsed = parse_dimacs(input.as_bytes(), &strict).unwrap();
+    assert_eq!(2, parsed.system.len());
+    assert!(parsed.system.clauses.contains(&last_clause));
+    assert_eq!(1, parsed.warnings.len());
+}
+
+#[test]
+fn test_parse_assignment() {
+    use dpll::assumption_core;
+
+    let options = ParseOptions::default();
+    let assignment = parse_assignment("c fixed by hand\n1 -3\n\n4 0\n".as_bytes(), &options);
+    assert_eq!(vec![1, -3, 4], assignment.unwrap());
+    assert_eq!(vec![2], parse_assignment("2".as_bytes(), &options).unwrap());
+    assert!(parse_assignment("2 0 3\n".as_bytes(), &options).is_err());
+    assert!(parse_assignment("2 0\n3\n".as_bytes(), &options).is_err());
+    assert!(parse_assignment("2 x\n".as_bytes(), &options).is_err());
+
+    // Fixing 1 and 4 forces 2 and 3 respectively, which can't both be true
+    let input = "p cnf 4 3\n-1 2 0\n-4 3 0\n-2 -3 0\n";
+    let system = parse_dimacs(input.as_bytes(), &options).unwrap().system;
+    let assignment = parse_assignment("1 -2 4 0\n".as_bytes(), &options).unwrap();
+    assert_eq!(Some(vec![1, 4]), assumption_core(&system, &assignment));
+    assert_eq!(Some(vec![1, -2]), assumption_core(&system, &[1, -2]));
+    assert_eq!(None, assumption_core(&system, &[1, -4]));
+}
+
+#[test]
+fn test_parse_dimacs_assumption_lines() {
+    use cnf_system::ClauseType;
+    use dpll::basic_dpll;
+
+    let input = "p cnf 3 3\n1 2 0\na 3 -1 0\n-2 -3 0\n-1 3 0\n";
+    let parsed = parse_dimacs(input.as_bytes(), &ParseOptions::default()).unwrap();
+    assert_eq!(vec![3, -1], parsed.assumptions);
+    assert_eq!(3, parsed.system.len());
+
+    // The system alone is Satisfiable, but not under the assumptions
+    let mut system = parsed.system.clone();
+    assert_eq!(ClauseType::Satisfiable, basic_dpll(system.clone()).0);
+    for &literal in &parsed.assumptions {
+        system.add_clause(CNFClause::from_literals(&[literal]).unwrap());
+    }
+    assert_eq!(ClauseType::Unsatisfiable, basic_dpll(system).0);
+
+    assert!(parse_dimacs("a 1 0 2\n".as_bytes(), &ParseOptions::default()).is_err());
+    let strict = ParseOptions{ strict: true, ..ParseOptions::default() };
+    assert!(parse_dimacs(input.as_bytes(), &strict).is_err());
+}
+
+#[test]
+fn test_parse_dimacs_plus_sign() {
+    let input = "p cnf 3 2\n+1 -2 0\n+3 0\n";
+    let parsed = parse_dimacs(input.as_bytes(), &ParseOptions::default()).unwrap();
+    assert_eq!(parse_dimacs("1 -2 0\n3 0\n".as_bytes(), &ParseOptions::default()).unwrap().system,
+               parsed.system);
+    assert!(parsed.units.contains(&3));
+
+    let options = ParseOptions{ strict: true, ..ParseOptions::default() };
+    match parse_dimacs(input.as_bytes(), &options) {
+        Err(ParseError::InvalidLine(line)) => assert_eq!("+1 -2 0", line),
+        other => panic!("expected InvalidLine, got {:?}", other),
+    }
+    assert!(parse_dimacs("1 +-2 0\n".as_bytes(), &ParseOptions::default()).is_err());
+}
+
+#[test]
+fn test_parse_dimacs_repeated_literals() {
+    // Clauses are sets, so a repeated literal only counts once, units included
+    let parsed = parse_dimacs("1 1 0\n".as_bytes(), &ParseOptions::default()).unwrap();
+    assert_eq!(vec![1], parsed.units.into_iter().collect::<Vec<_>>());
+    assert_eq!(Some(1), parsed.system.clauses.iter().next().unwrap().get_unit());
+
+    let parsed = parse_dimacs("1 -1 0\n2 2 -2 0\n".as_bytes(), &ParseOptions::default()).unwrap();
+    assert!(parsed.contains_tautologies);
+    assert!(parsed.system.is_empty() && parsed.units.is_empty());
+
+    let parsed = parse_dimacs("1 1 2 0\n2 1 0\n".as_bytes(), &ParseOptions::default()).unwrap();
+    assert!(parsed.units.is_empty());
+    assert_eq!(vec![2], parsed.system.clauses.iter().map(CNFClause::len).collect::<Vec<_>>());
+    assert_eq!(1, parsed.duplicate_clauses);
+}
+
+#[test]
+fn test_parse_dimacs_malformed_literals() {
+    for &(input, word) in &[("1 -0 0\n", "-0"), ("--3 1 0\n", "--3"), ("1 3- 0\n", "3-"),
+                            ("2 +0\n", "+0"), ("a 1 -0\n", "-0")] {
+        match parse_dimacs(input.as_bytes(), &ParseOptions::default()) {
+            Err(ParseError::InvalidLiteral{ literal, line }) => {
+                assert_eq!(word, literal);
+                assert_eq!(input.trim_end(), line);
+            },
+            other => panic!("expected InvalidLiteral for {}, got {:?}", word, other),
+        }
+    }
+    let error = parse_dimacs("1 -0 0\n".as_bytes(), &ParseOptions::default()).unwrap_err();
+    assert_eq!("not a valid literal: -0 in line: 1 -0 0", error.to_string());
+    assert!(parse_assignment("-0\n".as_bytes(), &ParseOptions::default()).is_err());
+
+    // Words that aren't numbers at all still make the line invalid, and a 0 still ends a clause
+    match parse_dimacs("1 x 0\n".as_bytes(), &ParseOptions::default()) {
+        Err(ParseError::InvalidLine(line)) => assert_eq!("1 x 0", line),
+        other => panic!("expected InvalidLine, got {:?}", other),
+    }
+    assert_eq!(2, parse_dimacs("1 0 -2 00\n".as_bytes(), &ParseOptions::default())
+                      .unwrap().system.len());
+}
+
+#[test]
+fn test_parse_dimacs_problem_line() {
+    let strict = ParseOptions{ strict: true, ..ParseOptions::default() };
+    let header = |input: &str, options: &ParseOptions| {
+        parse_dimacs(input.as_bytes(), options).map(|parsed| parsed.header)
+    };
+    let expected = Some(ProblemHeader{ vars: 5, clauses: 3 });
+    assert_eq!(expected, header("p cnf 5 3\n1 0\n", &strict).unwrap());
+    assert_eq!(expected, header("p  cnf   5   3\n1 0\n", &strict).unwrap());
+
+    // Other cases and extra fields are fine, unless --strict
+    for input in &["P CNF 5 3\n1 0\n", "p cnf 5 3 extra 7\n1 0\n", "p cnf v5 5 3\n1 0\n"] {
+        assert_eq!(expected, header(input, &ParseOptions::default()).unwrap());
+        assert!(header(input, &strict).is_err());
+    }
+
+    // A problem line that can't be read is ignored, unless --strict
+    assert_eq!(None, header("p cnf 5\n1 0\n", &ParseOptions::default()).unwrap());
+    assert_eq!(None, header("p wcnf 5 3\n1 0\n", &ParseOptions::default()).unwrap());
+    assert!(header("p cnf 5\n1 0\n", &strict).is_err());
+    assert_eq!(None, header("1 0\n", &strict).unwrap());
+}
+
+#[test]
+fn test_parse_dimacs_problem_line_counts() {
+    let strict = ParseOptions{ strict: true, ..ParseOptions::default() };
+    // Room for the declared clauses is made as soon as the problem line is read
+    let parsed = parse_dimacs("p cnf 3 100\n1 0\n".as_bytes(), &strict).unwrap();
+    assert!(parsed.system.clauses.capacity() >= 100);
+    assert_eq!(Some(ProblemHeader{ vars: 3, clauses: 100 }), parsed.header);
+    assert_eq!(vec!["the problem line declares 100 clauses but 1 was read"], parsed.warnings);
+    let parsed = parse_dimacs("p cnf 3 1\n1 0\n2 0\n".as_bytes(), &strict).unwrap();
+    assert_eq!(vec!["the problem line declares 1 clause but 2 were read"], parsed.warnings);
+
+    let parsed = parse_dimacs("p cnf 2 2\n1 -3 0\n2 0\n".as_bytes(), &strict).unwrap();
+    assert_eq!(vec!["the problem line declares 2 variables but variable 3 was read"],
+               parsed.warnings);
+    let parsed = parse_dimacs("p cnf 3 2\n1 -3 0\n2 0\n".as_bytes(), &strict).unwrap();
+    assert!(parsed.warnings.is_empty());
+    // Mismatched counts are only reported under --strict
+    let parsed = parse_dimacs("p cnf 1 5\n1 -3 0\n".as_bytes(), &ParseOptions::default());
+    assert!(parsed.unwrap().warnings.is_empty());
+
+    // Clauses that differ only in the order of their literals are merged
+    let input = "p cnf 3 4\n1 2 0\n-3 0\n2 1 0\n2 3 0\n";
+    let parsed = parse_dimacs(input.as_bytes(), &strict).unwrap();
+    assert_eq!(1, parsed.duplicate_clauses);
+    assert_eq!(3, parsed.system.len());
+    assert!(parsed.warnings.is_empty());
+    let parsed = parse_dimacs("p cnf 2 3\n1 2 0\n2 1 0\n".as_bytes(), &strict).unwrap();
+    assert_eq!(vec!["the problem line declares 3 clauses but 2 were read, 1 of them duplicates"],
+               parsed.warnings);
+
+    // Without a problem line, the input is read all the same
+    let parsed = parse_dimacs("1 -3 0\n2 0\n".as_bytes(), &strict).unwrap();
+    assert_eq!(None, parsed.header);
+    assert_eq!(2, parsed.system.len());
+}
+
+#[test]
+fn test_parse_icnf() {
+    use cnf_system::ClauseType;
+    use dpll::basic_dpll;
+
+    let input = "c incremental\np inccnf\n1 2 0\na -1 0\n-2\n3 0\na -1 -3 0\na 0\n";
+    let problem = parse_icnf(input.as_bytes(), &ParseOptions::default()).unwrap();
+    let clause = |literals: &[isize]| CNFClause::from_literals(literals).unwrap();
+    assert_eq!(vec![clause(&[1, 2]), clause(&[-2, 3])], problem.clauses);
+    assert_eq!(vec![IncrementalQuery{ clause_count: 1, assumptions: vec![-1] },
+                    IncrementalQuery{ clause_count: 2, assumptions: vec![-1, -3] },
+                    IncrementalQuery{ clause_count: 2, assumptions: vec![] }],
+               problem.queries);
+
+    // Solving each query in turn gives SAT, then UNSAT, then SAT without the assumptions
+    let verdicts = problem.queries.iter().map(|query| {
+        let mut system = CNFSystem::new(None);
+        for each_clause in &problem.clauses[..query.clause_count] {
+            system.add_clause(each_clause.clone());
+        }
+        for &literal in &query.assumptions {
+            system.add_clause(clause(&[literal]));
+        }
+        basic_dpll(system).0
+    }).collect::<Vec<_>>();
+    assert_eq!(vec![ClauseType::Satisfiable, ClauseType::Unsatisfiable, ClauseType::Satisfiable],
+               verdicts);
+
+    let strict = ParseOptions{ strict: true, ..ParseOptions::default() };
+    assert!(parse_icnf(input.as_bytes(), &strict).is_ok());
+    assert!(parse_icnf("P INCCNF\n1 0\n".as_bytes(), &ParseOptions::default()).is_ok());
+    assert!(parse_icnf("P INCCNF\n1 0\n".as_bytes(), &strict).is_err());
+    assert!(parse_icnf("p cnf 1 1\n1 0\n".as_bytes(), &strict).is_err());
+    assert!(parse_icnf("1 2\na 1 0\n".as_bytes(), &ParseOptions::default()).is_err());
+    assert!(parse_icnf("a 1 0 2\n".as_bytes(), &ParseOptions::default()).is_err());
+}
+
+#[test]
+fn test_parse_dimacs_keep_tautologies() {
+    let input = "p cnf 3 2\n1 -3 0\n2 -2 3 0\n";
+    let tautology = CNFClause::from_literals(&[2, -2, 3]).unwrap();
+    let options = ParseOptions{ keep_tautologies: true, ..ParseOptions::default() };
+    let mut parsed = parse_dimacs(input.as_bytes(), &options).unwrap();
+    assert!(parsed.contains_tautologies);
+    assert_eq!(2, parsed.system.len());
+    assert!(parsed.system.clauses.contains(&tautology));
+
+    assert_eq!(1, parsed.system.remove_tautologies());
+    assert_eq!(parse_dimacs(input.as_bytes(), &ParseOptions::default()).unwrap().system,
+               parsed.system);
+}
+
+#[test]
+fn test_parse_dimacs_satlib_ending() {
+    let input = "p cnf 3 2\n 1 -2 3 0\n-1 2 0\n%\n0\n\n";
+    let parsed = parse_dimacs(input.as_bytes(), &ParseOptions::default()).unwrap();
+    assert_eq!(2, parsed.system.len());
+    assert!(!parsed.system.clauses.contains(&CNFClause::new()));
+    assert!(parsed.warnings.is_empty());
+
+    let options = ParseOptions{ strict: true, ..ParseOptions::default() };
+    match parse_dimacs(input.as_bytes(), &options) {
+        Err(ParseError::InvalidLine(line)) => assert_eq!("%", line),
+        other => panic!("expected InvalidLine, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_dimacs_variable_too_large() {
+    let options = ParseOptions{ max_variable: 100, ..ParseOptions::default() };
+    assert!(parse_dimacs("1 -100 0\n".as_bytes(), &options).is_ok());
+    match parse_dimacs("1 -101 0\n".as_bytes(), &options) {
+        Err(ParseError::VariableTooLarge{ literal, max_variable }) => {
+            assert_eq!("-101", literal);
+            assert_eq!(100, max_variable);
+        },
+        other => panic!("expected VariableTooLarge, got {:?}", other),
+    }
+
+    // Literals that don't even fit in an isize are too large rather than invalid
+    let input = "1 99999999999999999999999 0\n";
+    match parse_dimacs(input.as_bytes(), &ParseOptions::default()) {
+        Err(ParseError::VariableTooLarge{ .. }) => {},
+        other => panic!("expected VariableTooLarge, got {:?}", other),
+    }
+    assert!(parse_dimacs("9999999999 0\n".as_bytes(), &ParseOptions::default()).is_err());
+}
+
+#[test]
+fn test_find_dimacs_files() {
+    use cnf_system::ClauseType;
+    use dpll::basic_dpll;
+
+    let directory = ::std::env::temp_dir().join(format!("rusat-test-{}", ::std::process::id()));
+    fs::create_dir_all(directory.join("nested")).unwrap();
+    // The xz fixture is only added with the "xz" feature
+    #[cfg_attr(not(feature = "xz"), allow(unused_mut))]
+    let mut fixtures = vec![
+        ("sat.cnf", b"p cnf 2 2\n1 2 0\n-1 0\n".to_vec(), ClauseType::Satisfiable),
+        ("nested/unsat.cnf", b"p cnf 1 2\n1 0\n-1 0\n".to_vec(), ClauseType::Unsatisfiable),
+    ];
+    #[cfg(feature = "xz")]
+    {
+        let mut compressed = vec![];
+        lzma_rs::xz_compress(&mut &b"p cnf 1 2\n1 0\n-1 0\n"[..], &mut compressed).unwrap();
+        assert!(compressed.starts_with(XZ_MAGIC));
+        fixtures.push(("nested/unsat.cnf.xz", compressed, ClauseType::Unsatisfiable));
+    }
+    for &(name, ref contents, _) in &fixtures {
+        fs::write(directory.join(name), contents).unwrap();
+    }
+    fs::write(directory.join("notes.txt"), "1 0\n").unwrap();
+    fs::write(directory.join("sat.cnf.bak"), "1 0\n").unwrap();
+    // A symlink back up the tree isn't walked into, which would never end
+    #[cfg(unix)]
+    ::std::os::unix::fs::symlink(&directory, directory.join("nested/loop")).unwrap();
+
+    let files = find_dimacs_files(&directory).unwrap();
+    let mut expected = fixtures.iter().map(|fixture| directory.join(fixture.0)).collect::<Vec<_>>();
+    expected.sort();
+    assert_eq!(expected, files);
+    // The files read back as they were written, giving the verdicts expected of them
+    for &(name, _, ref clause_type) in &fixtures {
+        let file = io::BufReader::new(fs::File::open(directory.join(name)).unwrap());
+        let input = decompress(file).unwrap();
+        let parsed = parse_dimacs(input, &ParseOptions::default()).unwrap();
+        assert_eq!(*clause_type, basic_dpll(parsed.system).0, "{}", name);
+    }
+
+    fs::remove_dir_all(&directory).unwrap();
+    assert!(find_dimacs_files(&directory).is_err());
+}
+
+#[test]
+fn test_decompress() {
+    let input = "c plain\np cnf 3 2\n1 -3 0\n2 0\n";
+    let options = ParseOptions::default();
+    let expected = parse_dimacs(input.as_bytes(), &options).unwrap();
+    let parsed = parse_dimacs(decompress(input.as_bytes()).unwrap(), &options).unwrap();
+    assert_eq!(expected.system, parsed.system);
+    assert_eq!(expected.header, parsed.header);
+    assert!(decompress(&b""[..]).is_ok());
+}
+
+#[cfg(feature = "xz")]
+#[test]
+fn test_decompress_xz() {
+    let input = "c compressed\np cnf 4 3\n1 -3 0\n2 4 0\n-1 0\n";
+    let mut compressed = vec![];
+    lzma_rs::xz_compress(&mut input.as_bytes(), &mut compressed).unwrap();
+    assert!(compressed.starts_with(XZ_MAGIC));
+
+    let options = ParseOptions::default();
+    let expected = parse_dimacs(input.as_bytes(), &options).unwrap();
+    let parsed = parse_dimacs(decompress(&compressed[..]).unwrap(), &options).unwrap();
+    assert_eq!(expected.system, parsed.system);
+    assert_eq!(expected.header, parsed.header);
+
+    // A stream cut short is an error rather than a truncated system
+    let truncated = &compressed[..compressed.len() / 2];
+    assert!(decompress(truncated).is_err());
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn test_decompress_gzip() {
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+
+    let input = "c compressed\np cnf 4 3\n1 -3 0\n2 4 0\n-1 0\n";
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(input.as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let options = ParseOptions::default();
+    let expected = parse_dimacs(input.as_bytes(), &options).unwrap();
+    let parsed = parse_dimacs(decompress(&compressed[..]).unwrap(), &options).unwrap();
+    assert_eq!(expected.system, parsed.system);
+}
+
+#[test]
+fn test_parse_solution() {
+    use testutil::system_from;
+
+    let system = system_from(&[&[1, -2], &[2, 3], &[-1, -3]]);
+    let solution = "c found by another solver\ns SATISFIABLE\nv 1 2\nv -3 0\n";
+    let model = parse_solution(solution.as_bytes(), &ParseOptions::default()).unwrap();
+    assert_eq!(vec![-3, 1, 2].into_iter().collect::<BTreeSet<_>>(), model);
+    assert!(system.is_satisfied_by(&model));
+
+    let model = parse_solution("s SATISFIABLE\nv -1 2 0\n".as_bytes(),
+                               &ParseOptions::default()).unwrap();
+    assert_eq!(Some(&CNFClause::from_literals(&[1, -2]).unwrap()),
+               system.unsatisfied_clause(&model));
+
+
+    // The clause is numbered by its place in the input, which the tautology and the duplicate
+    // count towards
+    let input = "p cnf 3 5\n2 3 0\n1 -1 0\n3 2 0\n-1 -3 0\n1 -2 0\n";
+    let options = ParseOptions{ keep_clause_numbers: true, ..ParseOptions::default() };
+    let parsed = parse_dimacs(input.as_bytes(), &options).unwrap();
+    assert_eq!(Some(5), parsed.first_unsatisfied_clause(&model));
+    let model = vec![1, 2, -3].into_iter().collect();
+    assert_eq!(None, parsed.first_unsatisfied_clause(&model));
+    let model = vec![1, 3].into_iter().collect();
+    assert_eq!(Some(4), parsed.first_unsatisfied_clause(&model));
+    let model = BTreeSet::new();
+    assert_eq!(Some(1), parsed.first_unsatisfied_clause(&model));
+
+    // Without the numbers there's no clause to report, but the system can still be checked
+    let parsed = parse_dimacs(input.as_bytes(), &ParseOptions::default()).unwrap();
+    assert!(parsed.clause_numbers.is_empty());
+    assert_eq!(None, parsed.first_unsatisfied_clause(&model));
+    assert!(parsed.system.unsatisfied_clause(&model).is_some());
+
+    assert!(parse_solution("1 2 0\n".as_bytes(), &ParseOptions::default()).is_err());
+    assert!(parse_solution("v 1 x 0\n".as_bytes(), &ParseOptions::default()).is_err());
+}
+
+#[test]
+fn test_format_model() {
+    let model: BTreeSet<isize> = vec![3, -2, 1, -10].into_iter().collect();
+    assert_eq!("{-10, -2, 1, 3}", format_model(&model, ModelFormat::Signed));
+    assert_eq!("1=true 2=false 3=true 10=false", format_model(&model, ModelFormat::Assign));
+    assert_eq!("", format_model(&BTreeSet::new(), ModelFormat::Assign));
+}
+
+#[test]
+fn test_write_dimacs_round_trip() {
+    let input = "c Generated by hand\nc\nc   seed: 42\np cnf 3 2\n1 -3 0\n2 0\n";
+    let options = ParseOptions{ keep_comments: true, ..ParseOptions::default() };
+    let parsed = parse_dimacs(input.as_bytes(), &options).unwrap();
+    assert_eq!(vec!["Generated by hand", "", "  seed: 42"], parsed.system.comments);
+
+    let mut output = vec![];
+    write_dimacs(&parsed.system, &mut output).unwrap();
+    let reparsed = parse_dimacs(&output[..], &options).unwrap();
+    assert_eq!(parsed.system, reparsed.system);
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("c Generated by hand\nc\nc   seed: 42\n"));
+
+    // Comments are left out unless they're asked for
+    let parsed = parse_dimacs(input.as_bytes(), &ParseOptions::default()).unwrap();
+    assert!(parsed.system.comments.is_empty());
+}
+
+#[test]
+fn test_write_dimacs_is_sorted() {
+    let parsed = parse_dimacs("3 -1 0\n2 0\n-2 4 0\n1 2 0\n".as_bytes(),
+                              &ParseOptions::default()).unwrap();
+    let mut output = vec![];
+    write_dimacs(&parsed.system, &mut output).unwrap();
+    assert_eq!("p cnf 4 4\n-2 4 0\n-1 3 0\n1 2 0\n2 0\n", String::from_utf8(output).unwrap());
+}
@@ -0,0 +1,194 @@
+use std::collections::BTreeSet;
+use assignment::Assignment;
+use cnf_system::{CNFClause, CNFSystem, ClauseType};
+
+/// A resolution proof that a system is Unsatisfiable. Clauses are referred to by id: the input
+/// clauses have the ids 0 to `inputs.len() - 1`, and each step's clause has the next id after
+/// them, in order. The last step derives the empty clause, unless an input clause is already empty
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Proof {
+    pub inputs: Vec<CNFClause>,
+    pub steps: Vec<ProofStep>,
+}
+
+/// A clause derived by resolving two earlier clauses of a proof
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofStep {
+    pub clause: CNFClause,
+    /// The ids of the clauses resolved, the first on the positive literal of the pivot
+    pub antecedents: [usize; 2],
+    /// The variable resolved on
+    pub pivot: usize,
+}
+
+impl Proof {
+    /// Returns the clause with the given id. Panics if there isn't one
+    pub fn clause(&self, id: usize) -> &CNFClause {
+        match id.checked_sub(self.inputs.len()) {
+            Some(step) => &self.steps[step].clause,
+            None       => &self.inputs[id],
+        }
+    }
+
+    /// Returns true if every step is the resolvent of its antecedents, which come before it, and
+    /// the proof ends in the empty clause
+    pub fn is_valid(&self) -> bool {
+        let steps_valid = self.steps.iter().enumerate().all(|(index, step)| {
+            let id = self.inputs.len() + index;
+            let [positive, negative] = step.antecedents;
+            let pivot = step.pivot as isize;
+            positive < id && negative < id
+                && self.clause(positive).contains(pivot) && self.clause(negative).contains(-pivot)
+                && self.clause(positive).resolve(self.clause(negative), pivot) == step.clause
+        });
+        let last_clause = match self.steps.last() {
+            Some(step) => step.clause.is_empty(),
+            None => self.inputs.iter().any(CNFClause::is_empty),
+        };
+        steps_valid && last_clause
+    }
+}
+
+/// Solves the system by DPLL, and if it's Unsatisfiable also returns a resolution proof of it.
+/// Each conflict is turned into a clause of negated decisions by resolving the clause that became
+/// empty with the clauses that propagated its literals, and the two clauses refuting the branches
+/// of a decision are resolved on its variable, so the refutation of the root is the empty clause.
+/// A branch whose clause doesn't mention its decision refutes the decision before it as well, so
+/// the other branch isn't searched. Returns a model instead if the system is Satisfiable.
+///
+/// This re-propagates from scratch at every branch, so it's only meant for small systems
+pub fn solve_with_proof_trace(system: &CNFSystem) -> (ClauseType, BTreeSet<isize>, Option<Proof>) {
+    let mut inputs = system.clauses.iter().cloned().collect::<Vec<_>>();
+    inputs.sort();
+    let mut proof = Proof{ inputs, steps: vec![] };
+    match refute(&mut proof, &mut vec![]) {
+        Ok(_)      => (ClauseType::Unsatisfiable, BTreeSet::new(), Some(proof)),
+        Err(model) => (ClauseType::Satisfiable, model, None),
+    }
+}
+
+/// Searches the branch reached by setting the decisions. Returns the id of a clause made up of
+/// negated decisions, which the branch makes false, or a model if the branch has one
+fn refute(proof: &mut Proof, decisions: &mut Vec<isize>) -> Result<usize, BTreeSet<isize>> {
+    let mut assignment = Assignment::new();
+    // Each literal set, along with the id of the clause that propagated it if it wasn't decided
+    let mut trail = vec![];
+    for &literal in decisions.iter() {
+        assignment.set(literal);
+        trail.push((literal, None));
+    }
+    if let Some(conflict) = propagate(&proof.inputs, &mut assignment, &mut trail) {
+        return Ok(analyze(proof, conflict, &trail));
+    }
+
+    let unsatisfied_clause = proof.inputs.iter().find(|clause| {
+        !clause.iter().any(|&literal| assignment.literal_value(literal) == Some(true))
+    });
+    let variable = match unsatisfied_clause {
+        Some(clause) => clause.iter().map(|literal| literal.unsigned_abs())
+                              .filter(|&variable| assignment.value(variable).is_none())
+                              .min().unwrap(),
+        None => return Err(assignment.to_model()),
+    };
+    let literal = variable as isize;
+    let mut refutations = vec![];
+    for &each_literal in &[literal, -literal] {
+        decisions.push(each_literal);
+        let refutation = refute(proof, decisions);
+        decisions.pop();
+        let refutation = refutation?;
+        if !proof.clause(refutation).contains(-each_literal) {
+            return Ok(refutation);
+        }
+        refutations.push(refutation);
+    }
+    Ok(resolve(proof, refutations[1], refutations[0], variable))
+}
+
+/// Propagates units until there are none left, adding the literals set to the trail. Returns the
+/// id of a clause every literal of which is false, if one is found
+fn propagate(inputs: &[CNFClause], assignment: &mut Assignment,
+             trail: &mut Vec<(isize, Option<usize>)>) -> Option<usize> {
+    'propagation: loop {
+        for (id, each_clause) in inputs.iter().enumerate() {
+            let mut open_literals = each_clause.iter().filter(|&&literal| {
+                assignment.literal_value(literal) != Some(false)
+            });
+            match (open_literals.next(), open_literals.next()) {
+                (None, _) => return Some(id),
+                (Some(&literal), None) if assignment.literal_value(literal).is_none() => {
+                    assignment.set(literal);
+                    trail.push((literal, Some(id)));
+                    continue 'propagation;
+                },
+                _ => {},
+            }
+        }
+        return None;
+    }
+}
+
+/// Resolves the clause that became empty with the clauses that propagated its literals, latest
+/// first, until only negated decisions are left. Returns the id of the clause that leaves
+fn analyze(proof: &mut Proof, conflict: usize, trail: &[(isize, Option<usize>)]) -> usize {
+    let mut clause = conflict;
+    for &(literal, reason) in trail.iter().rev() {
+        if let Some(reason) = reason {
+            if proof.clause(clause).contains(-literal) {
+                clause = if literal > 0 {
+                    resolve(proof, reason, clause, literal.unsigned_abs())
+                } else {
+                    resolve(proof, clause, reason, literal.unsigned_abs())
+                };
+            }
+        }
+    }
+    clause
+}
+
+/// Adds the resolvent of the clauses, the first with the positive literal of the pivot and the
+/// second with the negative one, as a step. Returns its id
+fn resolve(proof: &mut Proof, positive: usize, negative: usize, pivot: usize) -> usize {
+    let clause = proof.clause(positive).resolve(proof.clause(negative), pivot as isize);
+    proof.steps.push(ProofStep{ clause, antecedents: [positive, negative], pivot });
+    proof.inputs.len() + proof.steps.len() - 1
+}
+
+#[test]
+fn test_solve_with_proof_trace() {
+    use testutil::{pigeonhole, satisfies, system_from, XorShift, random_system};
+
+    let system = system_from(&[&[1, 2], &[1, -2], &[-1, 3], &[-1, -3]]);
+    let (clause_type, _, proof) = solve_with_proof_trace(&system);
+    assert_eq!(ClauseType::Unsatisfiable, clause_type);
+    let proof = proof.unwrap();
+    assert!(proof.is_valid());
+    assert_eq!(Some(&CNFClause::new()), proof.steps.last().map(|step| &step.clause));
+
+    let (clause_type, _, proof) = solve_with_proof_trace(&pigeonhole(3));
+    assert_eq!(ClauseType::Unsatisfiable, clause_type);
+    assert!(proof.unwrap().is_valid());
+
+    let mut rng = XorShift::new(196);
+    for _ in 0..20 {
+        let system = random_system(&mut rng, 8, 40, 3);
+        let (clause_type, model, proof) = solve_with_proof_trace(&system);
+        assert_eq!(::dpll::basic_dpll(system.clone()).0, clause_type);
+        match proof {
+            Some(proof) => assert!(proof.is_valid()),
+            None => assert!(satisfies(&system, &model)),
+        }
+    }
+
+    // An empty input clause is its own refutation
+    let (clause_type, _, proof) = solve_with_proof_trace(&system_from(&[&[1], &[]]));
+    assert_eq!(ClauseType::Unsatisfiable, clause_type);
+    let proof = proof.unwrap();
+    assert!(proof.steps.is_empty() && proof.is_valid());
+    assert_eq!(ClauseType::Satisfiable, solve_with_proof_trace(&CNFSystem::new(None)).0);
+
+    // A step that doesn't follow from its antecedents is caught
+    let mut proof = solve_with_proof_trace(&system).2.unwrap();
+    proof.steps[0].clause.add(5);
+    assert!(!proof.is_valid());
+}
@@ -0,0 +1,118 @@
+use std::io;
+use std::io::prelude::*;
+use cnf_system::ClauseType;
+
+/// One branch of a DPLL search
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchTreeNode {
+    /// The node this one branched off from, None for the root
+    pub parent: Option<usize>,
+    /// The literal decided on to reach this node, None for the root
+    pub decision: Option<isize>,
+    /// The literals set by propagating units and pure literals at this node, in order
+    pub propagated: Vec<isize>,
+    /// The verdict reached at this node, if the search ended here rather than branching further
+    pub result: Option<ClauseType>,
+}
+
+/// A record of every decision, propagation and backtrack made by a DPLL search, which can be
+/// drawn as a Graphviz graph. The tree grows with every branch explored, so it's only sensible for
+/// small systems
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchTree {
+    nodes: Vec<SearchTreeNode>,
+}
+
+impl SearchTree {
+    pub fn new() -> SearchTree {
+        SearchTree::default()
+    }
+
+    /// Adds a node branching off from `parent` by deciding `decision`, returning its index
+    pub fn add_node(&mut self, parent: Option<usize>, decision: Option<isize>) -> usize {
+        self.nodes.push(SearchTreeNode{ parent, decision, propagated: vec![], result: None });
+        self.nodes.len() - 1
+    }
+
+    /// Records that a literal was propagated at a node
+    pub fn add_propagation(&mut self, node: usize, literal: isize) {
+        self.nodes[node].propagated.push(literal);
+    }
+
+    /// Records that the search ended at a node with the given verdict
+    pub fn set_result(&mut self, node: usize, result: ClauseType) {
+        self.nodes[node].result = Some(result);
+    }
+
+    pub fn nodes(&self) -> &[SearchTreeNode] {
+        &self.nodes
+    }
+
+    /// Returns the amount of decisions made, i.e. every node but the root
+    pub fn decision_count(&self) -> usize {
+        self.nodes.iter().filter(|node| node.decision.is_some()).count()
+    }
+
+    /// Writes the tree as a Graphviz DOT graph. Decision nodes are labeled with their decision
+    /// literal followed by the literals propagated under it, and each node the search ended at
+    /// gets a leaf labeled with its verdict
+    pub fn write_dot<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        writeln!(output, "digraph search_tree {{")?;
+        for (index, each_node) in self.nodes.iter().enumerate() {
+            let mut label = match each_node.decision {
+                Some(literal) => literal.to_string(),
+                None          => "root".to_string(),
+            };
+            if !each_node.propagated.is_empty() {
+                let propagated = each_node.propagated.iter()
+                                                     .map(|literal| literal.to_string())
+                                                     .collect::<Vec<_>>();
+                label.push_str(&format!("\\n{}", propagated.join(" ")));
+            }
+            writeln!(output, "    n{} [label=\"{}\"];", index, label)?;
+            if let Some(parent) = each_node.parent {
+                writeln!(output, "    n{} -> n{};", parent, index)?;
+            }
+            if let Some(ref result) = each_node.result {
+                let result_label = match *result {
+                    ClauseType::Satisfiable   => "SAT",
+                    ClauseType::Unsatisfiable => "UNSAT",
+                    ClauseType::Tautology     => "TAUTOLOGY",
+                    ClauseType::Unknown       => "UNKNOWN",
+                };
+                writeln!(output, "    leaf{} [label=\"{}\", shape=box];", index, result_label)?;
+                writeln!(output, "    n{} -> leaf{};", index, index)?;
+            }
+        }
+        writeln!(output, "}}")
+    }
+}
+
+#[test]
+fn test_search_tree_write_dot() {
+    let mut tree = SearchTree::new();
+    let root = tree.add_node(None, None);
+    tree.add_propagation(root, 4);
+    let first_branch = tree.add_node(Some(root), Some(1));
+    tree.add_propagation(first_branch, -2);
+    tree.add_propagation(first_branch, 3);
+    tree.set_result(first_branch, ClauseType::Unsatisfiable);
+    let second_branch = tree.add_node(Some(root), Some(-1));
+    tree.set_result(second_branch, ClauseType::Satisfiable);
+    assert_eq!(2, tree.decision_count());
+
+    let mut output = vec![];
+    tree.write_dot(&mut output).unwrap();
+    assert_eq!("digraph search_tree {
+    n0 [label=\"root\\n4\"];
+    n1 [label=\"1\\n-2 3\"];
+    n0 -> n1;
+    leaf1 [label=\"UNSAT\", shape=box];
+    n1 -> leaf1;
+    n2 [label=\"-1\"];
+    n0 -> n2;
+    leaf2 [label=\"SAT\", shape=box];
+    n2 -> leaf2;
+}
+", String::from_utf8(output).unwrap());
+}
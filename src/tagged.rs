@@ -0,0 +1,94 @@
+use cnf_system::{CNFClause, CNFSystem};
+use dpll::assumption_core;
+use var_map::VarAllocator;
+
+/// A system whose clauses each carry a tag of the application's, e.g. the id of the constraint
+/// the clause came from, so that an unsatisfiable core can be given in terms of those tags. The
+/// clauses themselves stay plain CNFClauses, and the same clause can be added with several tags
+#[derive(Clone, Debug)]
+pub struct TaggedSystem<T> {
+    clauses: Vec<(CNFClause, T)>,
+}
+
+impl<T> Default for TaggedSystem<T> {
+    fn default() -> TaggedSystem<T> {
+        TaggedSystem{ clauses: vec![] }
+    }
+}
+
+impl<T: Clone> TaggedSystem<T> {
+    pub fn new() -> TaggedSystem<T> {
+        TaggedSystem::default()
+    }
+
+    pub fn add_clause_tagged(&mut self, clause: CNFClause, tag: T) {
+        self.clauses.push((clause, tag));
+    }
+
+    /// Returns the amount of clauses added, counting a clause once for every tag it was added with
+    pub fn len(&self) -> usize {
+        self.clauses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    /// Returns the system of the clauses without their tags
+    pub fn system(&self) -> CNFSystem {
+        let mut system = CNFSystem::new(None);
+        for (clause, _) in &self.clauses {
+            system.add_clause(clause.clone());
+        }
+        system
+    }
+
+    /// Returns None if the clauses are satisfiable. Otherwise returns the tags of an unsatisfiable
+    /// core of them, in the order they were added: a set of clauses that can't all be satisfied,
+    /// such that leaving out any one of them makes the rest satisfiable.
+    ///
+    /// Each clause gets a fresh selector variable s, and is relaxed to (¬s ∨ clause), so the core
+    /// is the core of the assumption that every selector is true. See `assumption_core`
+    pub fn core(&self) -> Option<Vec<T>> {
+        let mut allocator = VarAllocator::above([&self.system()]);
+        let mut relaxed_system = CNFSystem::new(None);
+        let mut selectors = vec![];
+        for (clause, _) in &self.clauses {
+            let selector = allocator.fresh() as isize;
+            let mut relaxed_clause = clause.clone();
+            relaxed_clause.add(-selector);
+            relaxed_system.add_clause(relaxed_clause);
+            selectors.push(selector);
+        }
+        let core = assumption_core(&relaxed_system, &selectors)?;
+        // Selectors are handed out in order, starting from the first one
+        Some(core.into_iter().map(|selector| {
+            self.clauses[(selector - selectors[0]) as usize].1.clone()
+        }).collect())
+    }
+}
+
+#[test]
+fn test_tagged_system_core() {
+    let clause = |literals: &[isize]| CNFClause::from_literals(literals).unwrap();
+    let mut system = TaggedSystem::new();
+    system.add_clause_tagged(clause(&[1, 2]), "x or y");
+    system.add_clause_tagged(clause(&[3]), "z");
+    system.add_clause_tagged(clause(&[-1]), "not x");
+    system.add_clause_tagged(clause(&[-3, 4]), "z implies w");
+    assert_eq!(4, system.len());
+    assert_eq!(None, system.core());
+
+    system.add_clause_tagged(clause(&[-2]), "not y");
+    assert_eq!(Some(vec!["x or y", "not x", "not y"]), system.core());
+
+    // The same clause under another tag is a separate clause of its own
+    let mut system = TaggedSystem::new();
+    system.add_clause_tagged(clause(&[1]), 1);
+    system.add_clause_tagged(clause(&[1]), 2);
+    system.add_clause_tagged(clause(&[-1]), 3);
+    assert_eq!(2, system.system().len());
+    let core = system.core().unwrap();
+    assert!(core == vec![1, 3] || core == vec![2, 3]);
+    assert_eq!(None, TaggedSystem::<usize>::new().core());
+}
@@ -0,0 +1,63 @@
+use std::collections::BTreeSet;
+use cnf_system::{CNFSystem, CNFClause};
+pub use rng::XorShift;
+
+/// Builds a random system of `clause_count` clauses over the variables 1..=`variable_count`, each
+/// with `clause_length` different variables, so that no clause is a tautology
+pub fn random_system(rng: &mut XorShift, variable_count: usize, clause_count: usize,
+                     clause_length: usize) -> CNFSystem {
+    let mut system = CNFSystem::new(None);
+    for _ in 0..clause_count {
+        let mut clause = CNFClause::new();
+        while clause.len() < clause_length {
+            let variable = (rng.below(variable_count as u64) + 1) as isize;
+            if !clause.contains_var(variable as usize) {
+                clause.add(if rng.below(2) == 0 { variable } else { -variable });
+            }
+        }
+        system.add_clause(clause);
+    }
+    system
+}
+
+/// Returns true if every clause in the system contains a literal of the interpretation
+pub fn satisfies(system: &CNFSystem, interpretation: &BTreeSet<isize>) -> bool {
+    system.is_satisfied_by(interpretation)
+}
+
+/// Builds a system from a list of clauses, each given as a list of literals
+pub fn system_from(clauses: &[&[isize]]) -> CNFSystem {
+    let mut system = CNFSystem::new(None);
+    for each_clause in clauses {
+        system.add_clause(CNFClause::from_literals(each_clause).unwrap());
+    }
+    system
+}
+
+/// Builds the pigeonhole problem of fitting `holes + 1` pigeons into `holes` holes, which is
+/// Unsatisfiable but takes DPLL exponentially long to prove. Variable `p * holes + h + 1` means
+/// pigeon p sits in hole h
+pub fn pigeonhole(holes: usize) -> CNFSystem {
+    let variable = |pigeon: usize, hole: usize| (pigeon * holes + hole + 1) as isize;
+    let mut system = CNFSystem::new(None);
+    // Every pigeon sits in some hole...
+    for pigeon in 0..holes + 1 {
+        let mut clause = CNFClause::new();
+        for hole in 0..holes {
+            clause.add(variable(pigeon, hole));
+        }
+        system.add_clause(clause);
+    }
+    // ...but no two pigeons share one
+    for hole in 0..holes {
+        for pigeon in 0..holes + 1 {
+            for other_pigeon in pigeon + 1..holes + 1 {
+                let mut clause = CNFClause::new();
+                clause.add(-variable(pigeon, hole));
+                clause.add(-variable(other_pigeon, hole));
+                system.add_clause(clause);
+            }
+        }
+    }
+    system
+}
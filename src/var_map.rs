@@ -0,0 +1,245 @@
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Range;
+use cnf_system::{CNFClause, CNFSystem};
+use rng::XorShift;
+
+/// Translates between the variable numbers of the input, which can be sparse (e.g. just 3, 70 and
+/// 1000), and dense 0-based indices given out in the order the variables are first seen. As a
+/// literal can't be 0, the internal literal of the variable with index i is ±(i + 1)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VarMap {
+    // The input variable of each index
+    variables: Vec<usize>,
+    indices: HashMap<usize, usize>,
+}
+
+impl VarMap {
+    pub fn new() -> VarMap {
+        VarMap::default()
+    }
+
+    /// Returns the index of an input variable, giving it the next free one if it's new
+    pub fn index_of(&mut self, variable: usize) -> usize {
+        if let Some(&index) = self.indices.get(&variable) {
+            return index;
+        }
+        let index = self.variables.len();
+        self.variables.push(variable);
+        self.indices.insert(variable, index);
+        index
+    }
+
+    /// Returns the input variable with the given index, if there is one
+    pub fn variable(&self, index: usize) -> Option<usize> {
+        self.variables.get(index).cloned()
+    }
+
+    /// Translates an input literal to its internal literal, giving its variable an index if needed
+    pub fn to_internal(&mut self, literal: isize) -> isize {
+        let internal_variable = (self.index_of(literal.unsigned_abs()) + 1) as isize;
+        internal_variable * literal.signum()
+    }
+
+    /// Translates an internal literal back to its input literal. Panics if no variable has been
+    /// given its index
+    pub fn to_dimacs(&self, literal: isize) -> isize {
+        let variable = self.variables[literal.unsigned_abs() - 1] as isize;
+        variable * literal.signum()
+    }
+
+    /// Returns a copy of an input system using internal literals
+    pub fn compact(&mut self, system: &CNFSystem) -> CNFSystem {
+        let mut compacted_system = CNFSystem::new(None);
+        for each_clause in &system.clauses {
+            let mut clause = CNFClause::new();
+            for &literal in each_clause.iter() {
+                clause.add(self.to_internal(literal));
+            }
+            compacted_system.add_clause(clause);
+        }
+        compacted_system.comments = system.comments.clone();
+        compacted_system
+    }
+
+    /// Returns a copy of a system using internal literals, translated back to input literals
+    pub fn expand(&self, system: &CNFSystem) -> CNFSystem {
+        let mut expanded_system = CNFSystem::new(None);
+        for each_clause in &system.clauses {
+            let mut clause = CNFClause::new();
+            for &literal in each_clause.iter() {
+                clause.add(self.to_dimacs(literal));
+            }
+            expanded_system.add_clause(clause);
+        }
+        expanded_system.comments = system.comments.clone();
+        expanded_system
+    }
+
+    /// Translates a model found for the internal system back to input literals
+    pub fn expand_model(&self, model: &BTreeSet<isize>) -> BTreeSet<isize> {
+        model.iter().map(|&literal| self.to_dimacs(literal)).collect()
+    }
+
+    /// Gives the variables that have an index a random order of indices instead, so that a
+    /// compacted system numbers its variables, and so orders its literals, differently. What the
+    /// system means is the same, so only the path the solver takes changes
+    pub fn shuffle(&mut self, rng: &mut XorShift) {
+        rng.shuffle(&mut self.variables);
+        for (index, &variable) in self.variables.iter().enumerate() {
+            self.indices.insert(variable, index);
+        }
+    }
+
+    /// Returns the amount of variables that have been given an index
+    pub fn len(&self) -> usize {
+        self.variables.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.variables.is_empty()
+    }
+}
+
+/// Hands out fresh variables, e.g. for the auxiliary variables of an encoding, each one above
+/// every variable handed out before. Encodings that share an allocator can't reuse each other's
+/// variables
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VarAllocator {
+    next_variable: usize,
+}
+
+impl VarAllocator {
+    /// Returns an allocator that starts at `first_variable`, or at 1 if that's 0
+    pub fn starting_at(first_variable: usize) -> VarAllocator {
+        VarAllocator{ next_variable: first_variable.max(1) }
+    }
+
+    /// Returns an allocator that starts above every variable of the systems
+    pub fn above<'a, I: IntoIterator<Item = &'a CNFSystem>>(systems: I) -> VarAllocator {
+        let last_variable = systems.into_iter()
+                                   .filter_map(|system| system.variables().last().cloned())
+                                   .max()
+                                   .unwrap_or(0);
+        VarAllocator::starting_at(last_variable + 1)
+    }
+
+    /// Returns the variable fresh() would return next
+    pub fn next_variable(&self) -> usize {
+        self.next_variable
+    }
+
+    /// Returns a variable that hasn't been handed out before. Panics if no variable is left whose
+    /// literals fit in an isize
+    pub fn fresh(&mut self) -> usize {
+        self.fresh_range(1).start
+    }
+
+    /// Returns `count` consecutive variables that haven't been handed out before. Panics if not
+    /// enough variables are left whose literals fit in an isize
+    pub fn fresh_range(&mut self, count: usize) -> Range<usize> {
+        let start = self.next_variable;
+        let end = start.checked_add(count)
+                       .filter(|&end| end - 1 <= isize::MAX as usize)
+                       .expect("ran out of variables to allocate");
+        self.next_variable = end;
+        start..end
+    }
+}
+
+#[test]
+fn test_var_map() {
+    let mut var_map = VarMap::new();
+    assert_eq!(0, var_map.index_of(1000));
+    assert_eq!(1, var_map.index_of(7));
+    assert_eq!(0, var_map.index_of(1000));
+    assert_eq!(Some(7), var_map.variable(1));
+    assert_eq!(None, var_map.variable(2));
+
+    assert_eq!(-2, var_map.to_internal(-7));
+    assert_eq!(3, var_map.to_internal(42));
+    assert_eq!(-42, var_map.to_dimacs(-3));
+    assert_eq!(1000, var_map.to_dimacs(1));
+    assert_eq!(3, var_map.len());
+}
+
+#[test]
+fn test_var_map_round_trip() {
+    use dpll::basic_dpll;
+    use cnf_system::ClauseType;
+    use parser::{parse_dimacs, ParseOptions};
+    use testutil::{satisfies, system_from};
+
+    let input = "p cnf 1000 3\n1000 -7 0\n7 42 0\n-1000 -42 3 0\n";
+    let mut parsed = parse_dimacs(input.as_bytes(), &ParseOptions::default()).unwrap();
+    assert_eq!(4, parsed.var_map.len());
+
+    let compacted_system = parsed.var_map.compact(&parsed.system);
+    assert_eq!(vec![1, 2, 3, 4], compacted_system.variables().into_iter().collect::<Vec<_>>());
+    assert_eq!(system_from(&[&[1, -2], &[2, 3], &[-1, -3, 4]]), compacted_system);
+    assert_eq!(parsed.system, parsed.var_map.expand(&compacted_system));
+
+    let (clause_type, model) = basic_dpll(compacted_system);
+    assert_eq!(ClauseType::Satisfiable, clause_type);
+    assert!(satisfies(&parsed.system, &parsed.var_map.expand_model(&model)));
+}
+
+#[test]
+fn test_var_map_shuffle() {
+    use cnf_system::ClauseType;
+    use dpll::basic_dpll;
+    use testutil::{random_system, satisfies, XorShift};
+
+    let mut rng = XorShift::new(180);
+    for _ in 0..10 {
+        let system = random_system(&mut rng, 12, 50, 3);
+        let mut var_map = VarMap::new();
+        let (clause_type, _) = basic_dpll(var_map.compact(&system));
+        for seed in 1..6 {
+            let mut shuffled_map = var_map.clone();
+            shuffled_map.shuffle(&mut XorShift::new(seed));
+            assert_eq!(var_map.len(), shuffled_map.len());
+            let shuffled_system = shuffled_map.compact(&system);
+            assert_eq!(system, shuffled_map.expand(&shuffled_system));
+
+            // Shuffling only renames the variables, so the verdict stays the same
+            let (shuffled_type, model) = basic_dpll(shuffled_system);
+            assert_eq!(clause_type, shuffled_type);
+            if shuffled_type == ClauseType::Satisfiable {
+                assert!(satisfies(&system, &shuffled_map.expand_model(&model)));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_var_allocator() {
+    use testutil::system_from;
+
+    let a = system_from(&[&[1, -4], &[2]]);
+    let b = system_from(&[&[-9, 3]]);
+    let mut allocator = VarAllocator::above([&a, &b]);
+    assert_eq!(10, allocator.fresh());
+    assert_eq!(11..14, allocator.fresh_range(3));
+    assert_eq!(14, allocator.next_variable());
+    assert_eq!(1, VarAllocator::above(&[]).fresh());
+    assert_eq!(1, VarAllocator::starting_at(0).fresh());
+
+    // Two encodings sharing an allocator get disjoint auxiliary variables
+    let first = a.negate_tseitin_with(&mut allocator);
+    let second = b.negate_tseitin_with(&mut allocator);
+    let auxiliary = |negation: &CNFSystem, system: &CNFSystem| {
+        negation.variables().difference(&system.variables()).cloned().collect::<BTreeSet<_>>()
+    };
+    let (first, second) = (auxiliary(&first, &a), auxiliary(&second, &b));
+    assert_eq!(vec![14, 15], first.iter().cloned().collect::<Vec<_>>());
+    assert_eq!(vec![16], second.iter().cloned().collect::<Vec<_>>());
+    assert_eq!(17, allocator.next_variable());
+}
+
+#[test]
+#[should_panic(expected = "ran out of variables")]
+fn test_var_allocator_overflow() {
+    let mut allocator = VarAllocator::starting_at(isize::MAX as usize);
+    allocator.fresh();
+    allocator.fresh();
+}
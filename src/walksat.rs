@@ -0,0 +1,242 @@
+use std::collections::{BTreeSet, HashMap};
+use assignment::Assignment;
+use cnf_system::{CNFSystem, ClauseType};
+use dpll::basic_dpll;
+use rng::XorShift;
+
+/// The noise and seed of hybrid_solve()'s walksat()
+const HYBRID_NOISE: f64 = 0.5;
+const HYBRID_SEED: u64 = 0x5EED;
+
+/// Looks for a model of the system by stochastic local search: starting from a random total
+/// assignment, it keeps picking an unsatisfied clause and flipping one of its variables, either
+/// one that breaks the fewest satisfied clauses or, with probability `noise`, a random one. After
+/// `max_flips` flips without finding a model it starts again from a new random assignment, up to
+/// `max_tries` times.
+///
+/// Returns Some(model) if it finds one, or None once it runs out of flips. It can never show that a
+/// system is Unsatisfiable, so it's only worth trying on systems that are likely Satisfiable.
+pub fn walksat(system: &CNFSystem, max_flips: usize, max_tries: usize, noise: f64, seed: u64)
+               -> Option<BTreeSet<isize>> {
+    if system.clauses.iter().any(|clause| clause.is_empty()) {
+        return None;
+    }
+    let mut rng = XorShift::new(seed);
+    let mut search = WalkSat::new(system);
+    for _ in 0..max_tries {
+        search.randomise(&mut rng);
+        for _ in 0..max_flips {
+            if search.unsatisfied.is_empty() {
+                break;
+            }
+            let clause = search.unsatisfied.pick(&mut rng);
+            let variable = search.pick_variable(clause, noise, &mut rng);
+            search.flip(variable);
+        }
+        if search.unsatisfied.is_empty() {
+            return Some(search.model());
+        }
+    }
+    None
+}
+
+/// Tries walksat() for up to `walk_budget` flips, which quickly finds models of easy Satisfiable
+/// systems, then falls back to basic_dpll() so that Unsatisfiable systems are still proved so
+pub fn hybrid_solve(system: CNFSystem, walk_budget: usize) -> (ClauseType, BTreeSet<isize>) {
+    match walksat(&system, walk_budget, 1, HYBRID_NOISE, HYBRID_SEED) {
+        Some(model) => (ClauseType::Satisfiable, model),
+        None        => basic_dpll(system),
+    }
+}
+
+/// The state of a walksat() search over one total assignment
+struct WalkSat {
+    clauses: Vec<Vec<isize>>,
+    // The clauses each literal occurs in
+    occurrences: HashMap<isize, Vec<usize>>,
+    variables: Vec<usize>,
+    values: Assignment,
+    // The amount of true literals in each clause
+    true_counts: Vec<usize>,
+    unsatisfied: ClauseList,
+}
+
+impl WalkSat {
+    fn new(system: &CNFSystem) -> WalkSat {
+        let clauses = system.clauses.iter()
+                                    .map(|clause| clause.iter().cloned().collect::<Vec<_>>())
+                                    .collect::<Vec<_>>();
+        let mut occurrences: HashMap<isize, Vec<usize>> = HashMap::new();
+        for (index, each_clause) in clauses.iter().enumerate() {
+            for &literal in each_clause {
+                occurrences.entry(literal).or_default().push(index);
+            }
+        }
+        let clause_count = clauses.len();
+        WalkSat {
+            clauses,
+            occurrences,
+            variables: system.variables().into_iter().collect(),
+            values: Assignment::new(),
+            true_counts: vec![0; clause_count],
+            unsatisfied: ClauseList::new(clause_count),
+        }
+    }
+
+    fn is_true(&self, literal: isize) -> bool {
+        self.values.literal_value(literal) == Some(true)
+    }
+
+    /// Returns the literal of the variable that is currently true
+    fn true_literal(&self, variable: usize) -> isize {
+        let literal = variable as isize;
+        if self.values.value(variable) == Some(true) { literal } else { -literal }
+    }
+
+    /// Gives every variable a random value and works out which clauses that leaves unsatisfied
+    fn randomise(&mut self, rng: &mut XorShift) {
+        for &variable in &self.variables {
+            let value = rng.below(2) == 0;
+            self.values.set(if value { variable as isize } else { -(variable as isize) });
+        }
+        self.unsatisfied.clear();
+        for index in 0..self.clauses.len() {
+            let true_count = self.clauses[index].iter()
+                                                .filter(|&&literal| self.is_true(literal))
+                                                .count();
+            self.true_counts[index] = true_count;
+            if true_count == 0 {
+                self.unsatisfied.insert(index);
+            }
+        }
+    }
+
+    /// Returns the amount of clauses that flipping the variable would leave unsatisfied, i.e. those
+    /// whose only true literal is the variable's
+    fn break_count(&self, variable: usize) -> usize {
+        self.occurrences.get(&self.true_literal(variable)).map_or(0, |clauses| {
+            clauses.iter().filter(|&&clause| self.true_counts[clause] == 1).count()
+        })
+    }
+
+    /// Picks the variable of an unsatisfied clause to flip next
+    fn pick_variable(&self, clause: usize, noise: f64, rng: &mut XorShift) -> usize {
+        let literals = &self.clauses[clause];
+        let (best_variable, best_break_count) = literals.iter()
+            .map(|literal| (literal.unsigned_abs(), self.break_count(literal.unsigned_abs())))
+            .min_by_key(|&(_, break_count)| break_count)
+            .unwrap();
+        // A flip that breaks nothing is always taken, otherwise sometimes take a random one to
+        // escape local minima
+        if best_break_count > 0 && rng.next_f64() < noise {
+            literals[rng.below(literals.len() as u64) as usize].unsigned_abs()
+        } else {
+            best_variable
+        }
+    }
+
+    fn flip(&mut self, variable: usize) {
+        let became_true = -self.true_literal(variable);
+        self.values.set(became_true);
+        let no_clauses = vec![];
+        for &clause in self.occurrences.get(&became_true).unwrap_or(&no_clauses) {
+            self.true_counts[clause] += 1;
+            if self.true_counts[clause] == 1 {
+                self.unsatisfied.remove(clause);
+            }
+        }
+        for &clause in self.occurrences.get(&-became_true).unwrap_or(&no_clauses) {
+            self.true_counts[clause] -= 1;
+            if self.true_counts[clause] == 0 {
+                self.unsatisfied.insert(clause);
+            }
+        }
+    }
+
+    fn model(&self) -> BTreeSet<isize> {
+        self.values.to_model()
+    }
+}
+
+/// A set of clause indices that can have clauses added, removed and picked at random in constant
+/// time
+struct ClauseList {
+    clauses: Vec<usize>,
+    // Where each clause is in `clauses`, if it's in the list
+    positions: Vec<Option<usize>>,
+}
+
+impl ClauseList {
+    fn new(clause_count: usize) -> ClauseList {
+        ClauseList{ clauses: vec![], positions: vec![None; clause_count] }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    fn clear(&mut self) {
+        for &clause in &self.clauses {
+            self.positions[clause] = None;
+        }
+        self.clauses.clear();
+    }
+
+    fn insert(&mut self, clause: usize) {
+        self.positions[clause] = Some(self.clauses.len());
+        self.clauses.push(clause);
+    }
+
+    fn remove(&mut self, clause: usize) {
+        let position = self.positions[clause].take().unwrap();
+        self.clauses.swap_remove(position);
+        if let Some(&moved_clause) = self.clauses.get(position) {
+            self.positions[moved_clause] = Some(position);
+        }
+    }
+
+    fn pick(&self, rng: &mut XorShift) -> usize {
+        self.clauses[rng.below(self.clauses.len() as u64) as usize]
+    }
+}
+
+#[test]
+fn test_walksat() {
+    use testutil::{random_system, satisfies, system_from};
+
+    let system = system_from(&[&[1, 2], &[-1, 3], &[-2, -3], &[2, 3, 4], &[-4, 1]]);
+    let model = walksat(&system, 1000, 10, 0.5, 129).expect("no model found");
+    assert!(satisfies(&system, &model));
+    assert_eq!(4, model.len());
+
+    // Random 3-SAT well below the satisfiability threshold is almost always Satisfiable, and easy
+    let mut rng = XorShift::new(129);
+    for _ in 0..20 {
+        let system = random_system(&mut rng, 40, 120, 3);
+        let model = walksat(&system, 10000, 10, 0.5, rng.next_u64()).expect("no model found");
+        assert!(satisfies(&system, &model));
+    }
+
+    // It gives up on Unsatisfiable systems rather than proving them so
+    let system = system_from(&[&[1, 2], &[-1, 2], &[1, -2], &[-1, -2]]);
+    assert_eq!(None, walksat(&system, 100, 3, 0.5, 1));
+    assert_eq!(None, walksat(&system_from(&[&[1], &[]]), 100, 3, 0.5, 1));
+    assert_eq!(Some(BTreeSet::new()), walksat(&CNFSystem::new(None), 100, 3, 0.5, 1));
+}
+
+#[test]
+fn test_hybrid_solve() {
+    use testutil::{pigeonhole, satisfies, system_from};
+
+    let system = system_from(&[&[1, 2], &[-1, 3], &[-2, -3], &[2, 3, 4], &[-4, 1]]);
+    let (clause_type, model) = hybrid_solve(system.clone(), 1000);
+    assert_eq!(ClauseType::Satisfiable, clause_type);
+    assert!(satisfies(&system, &model));
+
+    // With no flips at all, the model has to come from basic_dpll()
+    let (clause_type, model) = hybrid_solve(system.clone(), 0);
+    assert_eq!(ClauseType::Satisfiable, clause_type);
+    assert!(satisfies(&system, &model));
+
+    assert_eq!(ClauseType::Unsatisfiable, hybrid_solve(pigeonhole(3), 1000).0);
+}
@@ -0,0 +1,111 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+// Run the binary with the arguments and input, returning what it outputs
+fn rusat(args: &[&str], input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rusat"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    // Options like --dir don't read the input, and may have exited already
+    let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+    String::from_utf8(child.wait_with_output().unwrap().stdout).unwrap()
+}
+
+// Run the binary with the arguments and input, returning its exit code
+fn exit_code(args: &[&str], input: &str) -> Option<i32> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rusat"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+    child.wait().unwrap().code()
+}
+
+// A directory of its own for a test's files, emptied if it already exists
+fn scratch_directory(name: &str) -> PathBuf {
+    let directory = env::temp_dir().join(format!("rusat-cli-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+// The verdict the binary gives the input read from stdin, i.e. the first word of its last line
+fn verdict(args: &[&str], input: &str) -> String {
+    let output = rusat(&[&["-f", "-"], args].concat(), input);
+    let last_line = output.lines().last().unwrap_or("").to_string();
+    last_line.split([':', ' ']).next().unwrap().to_string()
+}
+
+// The verdict the binary gives the input as a file of a --dir batch
+fn batch_verdict(name: &str, input: &str) -> String {
+    let directory = scratch_directory(name);
+    fs::write(directory.join("input.cnf"), input).unwrap();
+    let output = rusat(&["--dir", directory.to_str().unwrap()], "");
+    fs::remove_dir_all(&directory).unwrap();
+    output.lines().next().unwrap().split(' ').nth(1).unwrap().to_string()
+}
+
+#[test]
+fn test_assumptions_without_clauses() {
+    // Only assumptions left, as the tautology is left out
+    let input = "p cnf 1 1\n1 -1 0\na 1 -1 0\n";
+    assert_eq!("UNSATISFIABLE", verdict(&[], input));
+    assert_eq!("UNSATISFIABLE", batch_verdict("tautology", input));
+
+    let input = "a 1 0\n";
+    assert_eq!("SATISFIABLE", verdict(&[], input));
+    assert_eq!("SATISFIABLE", batch_verdict("assumptions", input));
+
+    // Without assumptions, a system of tautologies is still a tautology
+    let input = "p cnf 1 1\n1 -1 0\n";
+    assert_eq!("TAUTOLOGY", verdict(&[], input));
+    assert_eq!("TAUTOLOGY", batch_verdict("only-tautology", input));
+}
+
+#[test]
+fn test_assign_without_clauses() {
+    let directory = scratch_directory("assign");
+    let assign_file = directory.join("assign.txt");
+    let assign_file = assign_file.to_str().unwrap();
+    let input = "p cnf 2 1\n1 -1 0\n";
+
+    // The system is empty once the tautology is left out, but the assignment still contradicts
+    fs::write(assign_file, "1 -1 0\n").unwrap();
+    assert_eq!("UNSATISFIABLE", verdict(&["--assign", assign_file], input));
+    fs::write(assign_file, "2 0\n").unwrap();
+    assert_eq!("SATISFIABLE: {2}", rusat(&["-f", "-", "--assign", assign_file], input).trim());
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_trivial_reason_uses_input_variables() {
+    let output = rusat(&["-f", "-", "-V", "1"], "p cnf 20 3\n1 2 0\n20 0\n-20 0\n");
+    assert!(output.lines().any(|line| line == "c trivial_reason=conflicting units 20 and -20"),
+            "{}", output);
+}
+
+#[test]
+fn test_shuffle_with_tree_or_trace() {
+    let directory = scratch_directory("shuffle");
+    let tree_file = directory.join("tree.dot");
+    let tree_file = tree_file.to_str().unwrap();
+    let input = "p cnf 2 2\n1 2 0\n-1 2 0\n";
+
+    // Neither solves with the shuffled numbering, so the combination is refused
+    assert_eq!(Some(22), exit_code(&["-f", "-", "--shuffle", "7", "--tree", tree_file], input));
+    assert!(!directory.join("tree.dot").exists());
+    assert_eq!(Some(22), exit_code(&["-f", "-", "--trace", "--shuffle", "7"], input));
+    assert_eq!(Some(0), exit_code(&["-f", "-", "--tree", tree_file], input));
+    assert_eq!(Some(0), exit_code(&["-f", "-", "--shuffle", "7"], input));
+    fs::remove_dir_all(&directory).unwrap();
+}